@@ -12,6 +12,192 @@ fn benchmark<T>(name: &str, f: impl FnOnce() -> T) -> T  {
     res
 }
 
+// shortest path core shared by the grid days, states are dense u32 ids chosen by the caller
+pub mod pathfind {
+    use arrayvec::ArrayVec;
+
+    pub const UNREACHED: u32 = u32::MAX;
+
+    // (state, cost) pairs written by the edge callback
+    pub type Edges = ArrayVec<(u32,u32),8>;
+
+    // dial's algorithm: a ring of buckets indexed by cost, wide enough that no edge can wrap around
+    pub struct BucketQueue {
+        buckets: Vec<Vec<u32>>,
+        mask: usize,
+        current: u32,
+        len: usize
+    }
+
+    impl BucketQueue {
+        pub fn new(max_edge_cost: u32) -> Self {
+            let ring_size = (max_edge_cost as usize + 1).next_power_of_two();
+            Self {
+                buckets: (0..ring_size).map(|_| Vec::with_capacity(64)).collect(),
+                mask: ring_size - 1,
+                current: 0,
+                len: 0
+            }
+        }
+
+        pub fn clear(&mut self) {
+            for bucket in self.buckets.iter_mut() {
+                bucket.clear();
+            }
+            self.current = 0;
+            self.len = 0;
+        }
+
+        #[inline(always)]
+        pub fn push(&mut self, cost: u32, state: u32) {
+            debug_assert!(cost >= self.current && ((cost - self.current) as usize) <= self.mask);
+            self.buckets[cost as usize & self.mask].push(state);
+            self.len += 1;
+        }
+
+        #[inline(always)]
+        pub fn pop(&mut self) -> Option<(u32,u32)> {
+            if self.len == 0 {
+                return None;
+            }
+            loop {
+                if let Some(state) = self.buckets[self.current as usize & self.mask].pop() {
+                    self.len -= 1;
+                    return Some((self.current,state));
+                }
+                self.current += 1;
+            }
+        }
+    }
+
+    pub struct Dijkstra {
+        pub dist: Vec<u32>,
+        queue: BucketQueue
+    }
+
+    impl Dijkstra {
+        pub fn new(state_count: usize, max_edge_cost: u32) -> Self {
+            Self {
+                dist: vec![UNREACHED;state_count],
+                queue: BucketQueue::new(max_edge_cost)
+            }
+        }
+
+        // fills dist from the start states, stopping early at the first goal state popped
+        pub fn search(
+            &mut self,
+            starts: impl IntoIterator<Item=u32>,
+            mut edges: impl FnMut(u32,&mut Edges),
+            mut goal: impl FnMut(u32) -> bool
+        ) -> Option<(u32,u32)> {
+            self.dist.fill(UNREACHED);
+            self.queue.clear();
+
+            for start in starts {
+                self.dist[start as usize] = 0;
+                self.queue.push(0, start);
+            }
+
+            let mut out = Edges::new();
+            while let Some((cost,state)) = self.queue.pop() {
+                // stale entry, state was already settled cheaper
+                if self.dist[state as usize] != cost {
+                    continue;
+                }
+                if goal(state) {
+                    return Some((state,cost));
+                }
+                out.clear();
+                edges(state,&mut out);
+                for (next,edge_cost) in out.iter().copied() {
+                    let next_cost = cost + edge_cost;
+                    if next_cost < self.dist[next as usize] {
+                        self.dist[next as usize] = next_cost;
+                        self.queue.push(next_cost, next);
+                    }
+                }
+            }
+            None
+        }
+    }
+}
+
+// simd search for several needles at once, shared by the text scanning days
+pub mod scan {
+    use core::simd::prelude::*;
+
+    const STRIDE: usize = 32;
+    // leading bytes of each needle that are compared with simd, the rest is confirmed per candidate
+    const PREFIX: usize = 4;
+
+    #[derive(Debug,Clone,Copy,PartialEq,Eq)]
+    pub struct Match {
+        // index into the scanner's needle list
+        pub needle: usize,
+        pub start: usize,
+        pub end: usize
+    }
+
+    pub struct Scanner<const N: usize> {
+        needles: [&'static [u8];N]
+    }
+
+    impl<const N: usize> Scanner<N> {
+        pub const fn new(needles: [&'static [u8];N]) -> Self {
+            let mut i = 0;
+            while i < N {
+                assert!(!needles[i].is_empty());
+                i += 1;
+            }
+            Self { needles }
+        }
+
+        // earliest match of any needle, ties go to the needle listed first
+        #[inline(always)]
+        pub fn find(&self, haystack: &[u8]) -> Option<Match> {
+            let mut offset = 0;
+            while haystack.len() - offset >= STRIDE + PREFIX - 1 {
+                let vectors: [u8x32;PREFIX] = std::array::from_fn(|k| u8x32::from_slice(&haystack[offset+k..]));
+
+                let mut candidates = 0;
+                for needle in self.needles.iter() {
+                    let mut mask = u32::MAX;
+                    for (k,b) in needle.iter().copied().take(PREFIX).enumerate() {
+                        mask &= vectors[k].simd_eq(u8x32::splat(b)).to_bitmask() as u32;
+                    }
+                    candidates |= mask;
+                }
+
+                while candidates != 0 {
+                    let index = offset + candidates.trailing_zeros() as usize;
+                    if let Some(found) = self.match_at(haystack, index) {
+                        return Some(found);
+                    }
+                    candidates &= candidates - 1;
+                }
+                offset += STRIDE;
+            }
+
+            // not enough bytes, slow path
+            (offset..haystack.len()).find_map(|index| self.match_at(haystack, index))
+        }
+
+        #[inline(always)]
+        fn match_at(&self, haystack: &[u8], index: usize) -> Option<Match> {
+            for (n,needle) in self.needles.iter().enumerate() {
+                if haystack[index..].starts_with(needle) {
+                    return Some(Match{
+                        needle: n,
+                        start: index,
+                        end: index + needle.len()
+                    });
+                }
+            }
+            None
+        }
+    }
+}
+
 pub mod day1 {
     fn parse_int(bytes: &[u8]) -> i32 {
         let a = (bytes[0] - 0x30) as i32 * 10000;
@@ -28,6 +214,54 @@ pub mod day1 {
         output_second: Vec<i32>
     }
 
+    // the real input is always "#####   #####\n", anything else takes the slow path
+    fn parse_fixed(input: &str, saved: &mut Common) -> bool {
+        if !input.len().is_multiple_of(14) {
+            return false;
+        }
+        for row in input.bytes().array_chunks::<14>() {
+            if row[5..8] != *b"   " || row[13] != b'\n' {
+                return false;
+            }
+            let num1 = parse_int(&row[0..5]);
+            let num2 = parse_int(&row[8..13]);
+            //let (num1,num2) = parse_chunk(&row);
+
+            saved.output_first.push(num1);
+            saved.output_second.push(num2);
+        }
+        true
+    }
+
+    // any digit widths, any non-digit separators, first number on a line goes left
+    fn parse_any(input: &str, saved: &mut Common) {
+        let mut n = 0;
+        let mut in_number = false;
+        let mut column = 0;
+        // trailing newline so the last number is always flushed
+        for b in input.bytes().chain(*b"\n") {
+            if b.is_ascii_digit() {
+                n = n * 10 + (b - b'0') as i32;
+                in_number = true;
+                continue;
+            }
+            if in_number {
+                if column == 0 {
+                    saved.output_first.push(n);
+                } else {
+                    saved.output_second.push(n);
+                }
+                column += 1;
+                n = 0;
+                in_number = false;
+            }
+            if b == b'\n' {
+                column = 0;
+            }
+        }
+        assert!(saved.output_first.len() == saved.output_second.len(), "every line needs two numbers");
+    }
+
     fn common(input: &str) -> Common {
         let mut saved = Common {
             output_first: Vec::with_capacity(1000),
@@ -35,13 +269,10 @@ pub mod day1 {
         };
 
         crate::benchmark("parse",|| {
-            for row in input.bytes().array_chunks::<14>() {
-                let num1 = parse_int(&row[0..5]);
-                let num2 = parse_int(&row[8..13]);
-                //let (num1,num2) = parse_chunk(&row);
-
-                saved.output_first.push(num1);
-                saved.output_second.push(num2);
+            if !parse_fixed(input, &mut saved) {
+                saved.output_first.clear();
+                saved.output_second.clear();
+                parse_any(input, &mut saved);
             }
         });
 
@@ -72,8 +303,17 @@ pub mod day1 {
         let mut sum = 0;
         let mut index = 0;
 
+        let mut prev = None;
+        let mut count = 0;
+
         for a in saved.output_first.iter().copied() {
-            let mut count = 0;
+            // repeated numbers on the left reuse the previous count, the right side is already consumed
+            if prev == Some(a) {
+                sum += count * a;
+                continue;
+            }
+            prev = Some(a);
+            count = 0;
             while index < saved.output_second.len() && saved.output_second[index] < a {
                 index += 1;
             }
@@ -124,25 +364,100 @@ pub mod day2 {
         }*/
     }
 
-    fn midwit_parse(input: &[u8]) -> ([u8;8],usize) {
+    // general path for reports that don't fit the i8x8 check: any length, any i32 levels
+    const WIDE_LANES: usize = 16;
+
+    fn check_wide(levels: &[i32]) -> (bool,u32) {
+        let delta_count = levels.len().saturating_sub(1);
+
+        let mut asc_okay = 0;
+        let mut desc_okay = 0;
+        let mut asc_broken = false;
+        let mut desc_broken = false;
+        let mut delta_sum = 0i64;
+
+        for start in (0..delta_count).step_by(WIDE_LANES) {
+            let count = (delta_count - start).min(WIDE_LANES) as u32;
+            // lanes past the end load as zero, giving a zero delta that is neither asc nor desc
+            let numbers = i32x16::load_or_default(&levels[start..start+count as usize]);
+            let numbers_shifted = i32x16::load_or_default(&levels[start+1..start+1+count as usize]);
+
+            let deltas = numbers_shifted - numbers;
+
+            let asc = (deltas.simd_le(i32x16::splat(3)) & deltas.simd_gt(i32x16::splat(0))).to_bitmask().trailing_ones().min(count);
+            let desc = (deltas.simd_ge(i32x16::splat(-3)) & deltas.simd_lt(i32x16::splat(0))).to_bitmask().trailing_ones().min(count);
+
+            if !asc_broken {
+                asc_okay += asc;
+                asc_broken = asc < count;
+            }
+            if !desc_broken {
+                desc_okay += desc;
+                desc_broken = desc < count;
+            }
+            delta_sum += deltas.cast::<i64>().reduce_sum();
+        }
+
+        let all = delta_count as u32;
+        ((asc_okay == all) | (desc_okay == all), if delta_sum > 0 { asc_okay } else { desc_okay })
+    }
+
+    fn wide_parse(input: &[u8], levels: &mut Vec<i32>) -> usize {
+        levels.clear();
         let mut i = 0;
-        let mut j = 0;
         let mut n = 0;
+
+        loop {
+            let byte = input[i];
+            match byte {
+                b'0'..=b'9' => {
+                    n = n * 10 + (byte - b'0') as i32;
+                }
+                b' ' => {
+                    levels.push(n);
+                    n = 0;
+                }
+                b'\n' => {
+                    levels.push(n);
+                    break;
+                }
+                _ => panic!("char {}",byte as char)
+            }
+            i += 1;
+        }
+
+        i
+    }
+
+    // levels past this can wrap into a valid delta against the 128 end marker
+    const NARROW_MAX: u32 = 99;
+
+    // None if the report is too long or has levels too big for i8x8
+    fn midwit_parse(input: &[u8]) -> Option<([u8;8],usize)> {
+        let mut i = 0;
+        let mut j = 0;
+        let mut n: u32 = 0;
         let mut result = [128;8];
 
         loop {
             let byte = input[i];
             match byte {
                 b'0'..=b'9' => {
-                    n = n * 10 + (byte - b'0');
+                    n = n * 10 + (byte - b'0') as u32;
+                    if n > NARROW_MAX {
+                        return None;
+                    }
                 }
                 b' ' => {
-                    result[j] = n;
+                    result[j] = n as u8;
                     n = 0;
                     j += 1;
+                    if j == 8 {
+                        return None;
+                    }
                 }
                 b'\n' => {
-                    result[j] = n;
+                    result[j] = n as u8;
                     break;
                 }
                 _ => panic!("char {}",byte as char)
@@ -150,10 +465,10 @@ pub mod day2 {
             i += 1;
         }
 
-        (result,i)
+        Some((result,i))
     }
 
-    fn fast_parse(input: &[u8]) -> ([u8;8],usize) {
+    fn fast_parse(input: &[u8]) -> Option<([u8;8],usize)> {
         {
             let newline = u8x32::splat(b'\n');
 
@@ -163,7 +478,9 @@ pub mod day2 {
                 return midwit_parse(input);
             };
 
-            let len = text.simd_eq(newline).first_set().unwrap();
+            let Some(len) = text.simd_eq(newline).first_set() else {
+                return midwit_parse(input);
+            };
 
             let (mask_spaces,mask_final) = match len {
                 // having more than 3 fast cases is slower
@@ -200,7 +517,29 @@ pub mod day2 {
 
             let res = mask_final.select(res,u8x8::splat(128));
 
-            (res.to_array(),len)
+            Some((res.to_array(),len))
+        }
+    }
+
+    enum Report {
+        Narrow([u8;8]),
+        // levels are in the shared buffer
+        Wide
+    }
+
+    #[inline(always)]
+    fn parse_report(input: &[u8], levels: &mut Vec<i32>) -> (Report,usize) {
+        match fast_parse(input) {
+            Some((nums,len)) => (Report::Narrow(nums),len),
+            None => (Report::Wide,wide_parse(input, levels))
+        }
+    }
+
+    #[inline(always)]
+    fn check_report(report: &Report, levels: &[i32]) -> (bool,u32) {
+        match report {
+            Report::Narrow(nums) => check_fast(&nums.map(|n| n as i8)),
+            Report::Wide => check_wide(levels)
         }
     }
 
@@ -208,23 +547,21 @@ pub mod day2 {
     unsafe fn impl1(input: &str) -> i32 {
         let mut bytes = input.as_bytes();
 
-        let mut line_count = 0;
+        let mut count = 0;
+        let mut levels = Vec::with_capacity(64);
 
         while bytes.len() > 0 {
-            let (nums,len) = fast_parse(bytes);
+            let (report,len) = parse_report(bytes, &mut levels);
             
-            let (okay,_) = check_fast(&std::mem::transmute(nums));
+            let (okay,_) = check_report(&report, &levels);
     
             if okay {
-                // once one is good, assume all others are good too
-                return 1000 - line_count;
+                count += 1;
             }
 
-            line_count += 1;
-
             bytes = &bytes[len+1..];
         }
-        0
+        count
     }
 
     fn slice_entry(mut entry: [u8;8], n: usize) -> [u8;8] {
@@ -235,21 +572,27 @@ pub mod day2 {
         entry
     }
 
+    fn slice_wide(levels: &[i32], n: usize, sliced: &mut Vec<i32>) {
+        sliced.clear();
+        sliced.extend_from_slice(&levels[..n]);
+        sliced.extend_from_slice(&levels[(n+1).min(levels.len())..]);
+    }
+
     #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
     unsafe fn impl2(input: &str) -> i32 {
         let mut bytes = input.as_bytes();
         let mut count = 0;
-        let mut line_count = 0;
+        let mut levels = Vec::with_capacity(64);
+        let mut sliced = Vec::with_capacity(64);
 
         while bytes.len() > 0 {
-            let (nums,len) = fast_parse(bytes);
+            let (report,len) = parse_report(bytes, &mut levels);
             
-            let (okay,fail) = check_fast(&std::mem::transmute(nums));
+            let (okay,fail) = check_report(&report, &levels);
     
             if okay {
-                // once one is good, assume all others are good too
-                return 1000 - line_count + count;
-            } else {
+                count += 1;
+            } else if let Report::Narrow(nums) = report {
                 // no significant perf impact of moving these behind branches
                 let sliced1 = slice_entry(nums,fail as usize);
                 let sliced2 = slice_entry(nums,fail as usize + 1);
@@ -257,21 +600,88 @@ pub mod day2 {
                 let (okay1,_) = check_fast(&std::mem::transmute(sliced1));
                 let (okay2,_) = check_fast(&std::mem::transmute(sliced2));
                 
+                if okay1 | okay2 {
+                    count += 1;
+                }
+            } else {
+                slice_wide(&levels, fail as usize, &mut sliced);
+                let (okay1,_) = check_wide(&sliced);
+                slice_wide(&levels, fail as usize + 1, &mut sliced);
+                let (okay2,_) = check_wide(&sliced);
+
                 if okay1 | okay2 {
                     count += 1;
                 }
             }
-            line_count += 1;
 
             bytes = &bytes[len+1..];
         }
 
         count
     }
+
+    #[derive(Debug,Clone,Copy,PartialEq,Eq)]
+    pub enum Safety {
+        Safe,
+        // index of the level the problem dampener removes
+        Fixable(usize),
+        Unsafe
+    }
+
+    // verdict for one report. tries the same removals part 2 does first, then every other level,
+    // so it's exact even where part 2's direction guess isn't
+    pub fn explain_report(levels: &[i32]) -> Safety {
+        let narrow = levels.len() <= 8 && levels.iter().all(|&n| (0..=NARROW_MAX as i32).contains(&n));
+        if narrow {
+            let mut nums = [128;8];
+            for (i,n) in levels.iter().copied().enumerate() {
+                nums[i] = n as u8;
+            }
+            let (okay,fail) = check_fast(&nums.map(|n| n as i8));
+            if okay {
+                return Safety::Safe;
+            }
+            let guesses = [fail as usize,fail as usize + 1];
+            for remove in guesses.into_iter().chain(0..levels.len()) {
+                let (okay,_) = check_fast(&slice_entry(nums,remove).map(|n| n as i8));
+                if okay {
+                    return Safety::Fixable(remove);
+                }
+            }
+        } else {
+            let (okay,fail) = check_wide(levels);
+            if okay {
+                return Safety::Safe;
+            }
+            let mut sliced = Vec::with_capacity(levels.len());
+            let guesses = [fail as usize,fail as usize + 1];
+            for remove in guesses.into_iter().chain(0..levels.len()) {
+                slice_wide(levels, remove, &mut sliced);
+                let (okay,_) = check_wide(&sliced);
+                if okay {
+                    return Safety::Fixable(remove);
+                }
+            }
+        }
+        Safety::Unsafe
+    }
+
+    // one verdict per line of the input
+    pub fn explain(input: &str) -> Vec<Safety> {
+        let mut levels = Vec::with_capacity(64);
+        input.lines().filter(|line| !line.is_empty()).map(|line| {
+            levels.clear();
+            levels.extend(line.split_ascii_whitespace().map(|n| n.parse::<i32>().unwrap()));
+            explain_report(&levels)
+        }).collect()
+    }
 }
 
 pub mod day3 {
-    use core::{cmp::Ord, iter::Iterator, simd::prelude::*};
+    use core::{iter::Iterator, simd::prelude::*};
+    use std::io::{self, Read};
+
+    use crate::scan::Scanner;
 
     pub fn part1(input: &str) -> i64 {
         unsafe { impl1(input) }
@@ -355,98 +765,10 @@ pub mod day3 {
         Some(n1*n2)
     }
 
-    #[inline(always)]
-    unsafe fn scan4(haystack: &[u8], needle_bytes: [u8;4]) -> Option<usize> {
-        const STRIDE: usize = 32;
-
-        let needle = i32x8::splat(i32::from_le_bytes(needle_bytes));
-
-        for offset in (0..).step_by(STRIDE) {
-            let chunk0 = &haystack[offset..];
-            // not enough bytes, slow path
-            if chunk0.len() < STRIDE+3 {
-                for (i,window) in chunk0.windows(4).enumerate() {
-                    if window == needle_bytes {
-                        return Some(offset + i + 4);
-                    }
-                }
-                return None;
-            }
-            let vector0: i32x8 = std::mem::transmute( u8x32::from_slice(chunk0) );
-            let vector1: i32x8 = std::mem::transmute( u8x32::from_slice(&haystack[offset+1..]) );
-            let vector2: i32x8 = std::mem::transmute( u8x32::from_slice(&haystack[offset+2..]) );
-            let vector3: i32x8 = std::mem::transmute( u8x32::from_slice(&haystack[offset+3..]) );
-    
-            let offset0 = needle.simd_eq(vector0).to_bitmask().trailing_zeros()*4;
-            let offset1 = needle.simd_eq(vector1).to_bitmask().trailing_zeros()*4+1;
-            let offset2 = needle.simd_eq(vector2).to_bitmask().trailing_zeros()*4+2;
-            let offset3 = needle.simd_eq(vector3).to_bitmask().trailing_zeros()*4+3;
-    
-            let index = offset0.min(offset1).min(offset2.min(offset3)) as usize;
-            
-            if index < 256 {
-                return Some(offset + index + 4);
-            }
-        }
-        None
-    }
-
-    #[inline(always)]
-    unsafe fn scan4x2(haystack: &[u8], needle_bytes_1: [u8;4], needle_bytes_2: [u8;4]) -> (Option<usize>,Option<usize>) {
-        const STRIDE: usize = 32;
-
-        let needle1 = i32x8::splat(i32::from_le_bytes(needle_bytes_1));
-        let needle2 = i32x8::splat(i32::from_le_bytes(needle_bytes_2));
-
-        for offset in (0..).step_by(STRIDE) {
-            let chunk0 = &haystack[offset..];
-            // not enough bytes, slow path
-            if chunk0.len() < STRIDE+3 {
-                let mut ret1 = None;
-                let mut ret2 = None;
-
-                for (i,window) in chunk0.windows(4).enumerate() {
-                    if window == needle_bytes_1 {
-                        ret1 = Some(offset + i + 4);
-                        break;
-                    }
-                }
-
-                for (i,window) in chunk0.windows(4).enumerate() {
-                    if window == needle_bytes_2 {
-                        ret2 = Some(offset + i + 4);
-                        break;
-                    }
-                }
-
-                return (ret1,ret2);
-            }
-            let vector0: i32x8 = std::mem::transmute( u8x32::from_slice(chunk0) );
-            let vector1: i32x8 = std::mem::transmute( u8x32::from_slice(&haystack[offset+1..]) );
-            let vector2: i32x8 = std::mem::transmute( u8x32::from_slice(&haystack[offset+2..]) );
-            let vector3: i32x8 = std::mem::transmute( u8x32::from_slice(&haystack[offset+3..]) );
-    
-            let n1_offset0 = needle1.simd_eq(vector0).to_bitmask().trailing_zeros()*4;
-            let n1_offset1 = needle1.simd_eq(vector1).to_bitmask().trailing_zeros()*4+1;
-            let n1_offset2 = needle1.simd_eq(vector2).to_bitmask().trailing_zeros()*4+2;
-            let n1_offset3 = needle1.simd_eq(vector3).to_bitmask().trailing_zeros()*4+3;
-
-            let n2_offset0 = needle2.simd_eq(vector0).to_bitmask().trailing_zeros()*4;
-            let n2_offset1 = needle2.simd_eq(vector1).to_bitmask().trailing_zeros()*4+1;
-            let n2_offset2 = needle2.simd_eq(vector2).to_bitmask().trailing_zeros()*4+2;
-            let n2_offset3 = needle2.simd_eq(vector3).to_bitmask().trailing_zeros()*4+3;
-    
-            let index_1 = n1_offset0.min(n1_offset1).min(n1_offset2.min(n1_offset3)) as usize;
-            let index_2 = n2_offset0.min(n2_offset1).min(n2_offset2.min(n2_offset3)) as usize;
-            
-            if index_1 < 256 || index_2 < 256 {
-                let ret1 = if index_1 < 256 { Some(offset + index_1 + 4) } else { None };
-                let ret2 = if index_2 < 256 { Some(offset + index_2 + 4) } else { None };
-                return (ret1,ret2);
-            }
-        }
-        (None,None)
-    }
+    static MUL: Scanner<1> = Scanner::new([b"mul("]);
+    // while enabled we need both, while disabled only do() matters
+    static ENABLED: Scanner<2> = Scanner::new([b"mul(",b"don't()"]);
+    static DISABLED: Scanner<1> = Scanner::new([b"do()"]);
 
     #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
     unsafe fn impl1(input: &str) -> i64 {
@@ -454,8 +776,8 @@ pub mod day3 {
 
         let mut sum = 0;
 
-        while let Some(index) = scan4(input,*b"mul(") {
-            input = &input[index..];
+        while let Some(found) = MUL.find(input) {
+            input = &input[found.end..];
             if let Some(product) = parse( input ) {
                 sum += product;
             }
@@ -469,70 +791,257 @@ pub mod day3 {
 
         let mut sum = 0;
 
-        loop {
-            let (mul_index,dont_index) = scan4x2(input,*b"mul(",*b"don'");
-            match (mul_index,dont_index) {
-                (Some(mul_index),Some(_dont_index)) if mul_index < _dont_index => {
-                    input = &input[mul_index..];
-                    if let Some(product) = parse( input ) {
-                        sum += product;
-                    }
-                }
-                (_,Some(dont_index)) => {
-                    input = &input[dont_index..];
-                    if input.len() > 3 && input[0] == b't' && input[1] == b'(' &&  input[2] == b')' {
-                        // skip until do
-                        if let Some(index) = scan4(input,*b"do()") {
-                            input = &input[index..];
-                        } else {
-                            break;
-                        }
-                    }
-                }
-                (Some(mul_index),None) => {
-                    input = &input[mul_index..];
-                    if let Some(product) = parse( input ) {
-                        sum += product;
-                    }
-                }
-                (None,None) => {
-                    break;
+        while let Some(found) = ENABLED.find(input) {
+            input = &input[found.end..];
+            if found.needle == 0 {
+                if let Some(product) = parse( input ) {
+                    sum += product;
                 }
+            } else {
+                // skip until do
+                let Some(found) = DISABLED.find(input) else { break };
+                input = &input[found.end..];
             }
         }
         return sum;
     }
-}
 
-pub mod day4 {
-    // 140 = (32 + 32 + 32 + 32) + (8 + 4)
-    // 140 = (64 + 64) + (try 16)
+    #[derive(Debug,Clone,Copy,PartialEq,Eq)]
+    pub enum Reject {
+        // nothing between "mul(" and the comma
+        EmptyFirst,
+        // nothing between the comma and ")"
+        EmptySecond,
+        // a byte that can't appear at this point in the instruction
+        Unexpected(u8),
+        // input ended before the second number
+        Truncated
+    }
 
-    use core::simd::prelude::*;
+    #[derive(Debug,Clone,Copy,PartialEq,Eq)]
+    pub enum Instr {
+        // offsets are where the instruction's name starts
+        Mul{ a: i64, b: i64, offset: usize, enabled: bool },
+        Do{ offset: usize },
+        Dont{ offset: usize },
+        Rejected{ offset: usize, reason: Reject }
+    }
 
-    static mut GLOBAL: Globals = Globals{
-        x_0: Grid::empty(),
-        m_0: Grid::empty(),
-        a_0: Grid::empty(),
-        s_0: Grid::empty(),
-    };
+    // every instruction in the order the fast path meets them, muls in disabled
+    // regions are still listed but marked as such
+    pub fn trace(input: &str) -> Trace<'_> {
+        Trace {
+            input: input.as_bytes(),
+            pos: 0,
+            enabled: true
+        }
+    }
 
-    #[derive(Copy,Clone)]
-    // (high word,low word)
-    struct Row(u16,u128);
+    static TRACE: Scanner<3> = Scanner::new([b"mul(",b"don't()",b"do()"]);
 
-    impl Row {
-        fn new(bytes: &[u8], char: u8) -> Self {
-            assert!(bytes.len() >= 140);
+    pub struct Trace<'a> {
+        input: &'a [u8],
+        pos: usize,
+        enabled: bool
+    }
 
-            let char_splat = u8x32::splat(char);
+    // same acceptance rules as the scalar half of parse, but keeping the operands.
+    // like parse, input ending after the second number still counts, the flag says if ")" was seen
+    fn parse_args(bytes: &[u8]) -> Result<(i64,i64,bool),Reject> {
+        let mut iter = bytes.iter().copied();
 
-            let byte_vector = u8x32::from_slice(bytes);
-            let mut low = byte_vector.simd_eq(char_splat).to_bitmask() as u128;
+        let mut digit_count = 0;
+        let mut n1 = 0;
+        loop {
+            match iter.next() {
+                Some(d @ b'0'..=b'9') => {
+                    n1 = n1 * 10 + (d - b'0') as i64;
+                    digit_count += 1;
+                }
+                Some(b',') => break,
+                Some(d) => return Err(Reject::Unexpected(d)),
+                None => return Err(Reject::Truncated)
+            }
+        }
 
-            let byte_vector = u8x32::from_slice(&bytes[32..]);
-            low |= (byte_vector.simd_eq(char_splat).to_bitmask() as u128) << 32;
-            
+        if digit_count == 0 {
+            return Err(Reject::EmptyFirst);
+        }
+
+        let mut digit_count = 0;
+        let mut n2 = 0;
+        let mut closed = false;
+        for d in iter {
+            match d {
+                b'0'..=b'9' => {
+                    n2 = n2 * 10 + (d - b'0') as i64;
+                    digit_count += 1;
+                }
+                b')' => {
+                    closed = true;
+                    break;
+                }
+                _ => return Err(Reject::Unexpected(d))
+            }
+        }
+
+        if digit_count == 0 {
+            return Err(if closed { Reject::EmptySecond } else { Reject::Truncated });
+        }
+
+        Ok((n1,n2,closed))
+    }
+
+    impl Iterator for Trace<'_> {
+        type Item = Instr;
+
+        fn next(&mut self) -> Option<Instr> {
+            let found = TRACE.find(&self.input[self.pos..])?;
+            let offset = self.pos + found.start;
+            self.pos += found.end;
+
+            Some(match found.needle {
+                0 => match parse_args(&self.input[self.pos..]) {
+                    Ok((a,b,_)) => {
+                        debug_assert_eq!(unsafe { parse(&self.input[self.pos..]) }, Some(a*b));
+                        Instr::Mul{ a, b, offset, enabled: self.enabled }
+                    }
+                    Err(reason) => Instr::Rejected{ offset, reason }
+                },
+                1 => {
+                    self.enabled = false;
+                    Instr::Dont{ offset }
+                }
+                _ => {
+                    self.enabled = true;
+                    Instr::Do{ offset }
+                }
+            })
+        }
+    }
+
+    pub const STREAM_CHUNK: usize = 1 << 16;
+    // longest needle minus one, enough to catch a needle split across two chunks
+    const NEEDLE_OVERLAP: usize = 6;
+
+    pub fn part1_read(reader: impl Read) -> io::Result<i64> {
+        evaluate_read(reader, false, STREAM_CHUNK)
+    }
+
+    pub fn part2_read(reader: impl Read) -> io::Result<i64> {
+        evaluate_read(reader, true, STREAM_CHUNK)
+    }
+
+    // fills as much of buf as the reader allows, short only at end of input
+    fn read_full(reader: &mut impl Read, mut buf: &mut [u8]) -> io::Result<usize> {
+        let mut total = 0;
+        while !buf.is_empty() {
+            match reader.read(buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    total += n;
+                    buf = &mut buf[n..];
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e)
+            }
+        }
+        Ok(total)
+    }
+
+    // same results as part1/part2 for any chunk size, reading the input chunk bytes at a time.
+    // state carried between chunks is the do/don't flag plus the unconsumed tail,
+    // which is either a possibly split needle or a mul( whose arguments aren't complete yet.
+    // memory stays around two chunks, unless one instruction's digits run on for longer than that
+    pub fn evaluate_read(mut reader: impl Read, conditionals: bool, chunk: usize) -> io::Result<i64> {
+        assert!(chunk > NEEDLE_OVERLAP);
+        let mut buffer = vec![0u8;chunk*2];
+        let mut len = 0;
+        let mut enabled = true;
+        let mut sum = 0;
+
+        loop {
+            // a pending mul( can outgrow the buffer, give it another chunk of room
+            if buffer.len() < len + chunk {
+                buffer.resize(len + chunk, 0);
+            }
+            let read = read_full(&mut reader, &mut buffer[len..len+chunk])?;
+            let eof = read < chunk;
+            len += read;
+
+            let data = &buffer[..len];
+            let mut pos = 0;
+            let mut pending = None;
+
+            loop {
+                if enabled {
+                    let Some(found) = ENABLED.find(&data[pos..]) else { break };
+                    let start = pos + found.start;
+                    pos += found.end;
+                    if found.needle == 0 {
+                        let args = parse_args(&data[pos..]);
+                        let incomplete = matches!(args, Err(Reject::Truncated) | Ok((_,_,false)));
+                        if incomplete && !eof {
+                            // retry once the rest of it has been read
+                            pending = Some(start);
+                            break;
+                        }
+                        // only the end of the input may cut the arguments short
+                        if let Ok((a,b,closed)) = args {
+                            if closed || eof {
+                                sum += a*b;
+                            }
+                        }
+                    } else if conditionals {
+                        enabled = false;
+                    }
+                } else {
+                    let Some(found) = DISABLED.find(&data[pos..]) else { break };
+                    pos += found.end;
+                    enabled = true;
+                }
+            }
+
+            if eof {
+                return Ok(sum);
+            }
+
+            let keep_from = pending.unwrap_or(pos.max(len.saturating_sub(NEEDLE_OVERLAP)));
+            buffer.copy_within(keep_from..len, 0);
+            len -= keep_from;
+        }
+    }
+}
+
+pub mod day4 {
+    // 140 = (32 + 32 + 32 + 32) + (8 + 4)
+    // 140 = (64 + 64) + (try 16)
+
+    use core::simd::prelude::*;
+
+    static mut GLOBAL: Globals = Globals{
+        x_0: Grid::empty(),
+        m_0: Grid::empty(),
+        a_0: Grid::empty(),
+        s_0: Grid::empty(),
+    };
+
+    #[derive(Copy,Clone)]
+    // (high word,low word)
+    struct Row(u16,u128);
+
+    impl Row {
+        fn new(bytes: &[u8], char: u8) -> Self {
+            assert!(bytes.len() >= 140);
+
+            let char_splat = u8x32::splat(char);
+
+            let byte_vector = u8x32::from_slice(bytes);
+            let mut low = byte_vector.simd_eq(char_splat).to_bitmask() as u128;
+
+            let byte_vector = u8x32::from_slice(&bytes[32..]);
+            low |= (byte_vector.simd_eq(char_splat).to_bitmask() as u128) << 32;
+            
             let byte_vector = u8x32::from_slice(&bytes[64..]);
             low |= (byte_vector.simd_eq(char_splat).to_bitmask() as u128) << 64;
 
@@ -756,11 +1265,51 @@ pub mod day5 {
     use core::iter::Iterator;
 
     pub fn part1(input: &str) -> i64 {
-        unsafe { impl1(input) }
+        if is_two_digit(input.as_bytes()) {
+            unsafe { impl1(input) }
+        } else {
+            general1(input)
+        }
     }
 
     pub fn part2(input: &str) -> i64 {
-        unsafe { impl2(input) }
+        if is_two_digit(input.as_bytes()) {
+            unsafe { impl2(input) }
+        } else {
+            general2(input)
+        }
+    }
+
+    // the fast path needs every page to be exactly two digits, which also keeps them under 128.
+    // looks for digit runs of length 1 or 3+, windows overlap so a run can't hide on a boundary
+    fn is_two_digit(bytes: &[u8]) -> bool {
+        const WIDTH: usize = 64;
+        let mut offset = 0;
+        loop {
+            let rest = &bytes[offset..];
+            // bytes past the end load as zero, which isn't a digit
+            let vec = u8x64::load_or_default(rest);
+            let digits = (vec.simd_ge(u8x64::splat(b'0')) & vec.simd_le(u8x64::splat(b'9'))).to_bitmask();
+            let triple = digits & (digits << 1) & (digits >> 1);
+            let single = digits & !(digits << 1) & !(digits >> 1);
+
+            // edge bits only see one neighbour, unless they're at the edge of the input too
+            let mut valid = u64::MAX;
+            if offset != 0 {
+                valid &= !1;
+            }
+            if rest.len() > WIDTH {
+                valid &= !(1 << 63);
+            }
+
+            if (triple | single) & valid != 0 {
+                return false;
+            }
+            if rest.len() <= WIDTH {
+                return true;
+            }
+            offset += WIDTH - 2;
+        }
     }
 
     // array bitfields that represent which numbers can NOT come before a given number
@@ -968,6 +1517,276 @@ pub mod day5 {
         }
         sum
     }
+
+    // ordering rules for any page numbers, stored as one bitset row per page.
+    // same meaning as RULES: the row for a page holds the pages that can NOT come before it
+    #[derive(Clone)]
+    pub struct Rules {
+        words: usize,
+        pages: usize,
+        bits: Vec<u64>,
+    }
+
+    impl Rules {
+        // rules for pages 0..pages
+        pub fn new(pages: usize) -> Self {
+            let words = pages.div_ceil(64);
+            Self{words, pages, bits: vec![0;words*pages]}
+        }
+
+        // parses the rule section, sized by the largest page anywhere in the input.
+        // returns the rules and the remaining update lines
+        pub fn parse(input: &str) -> (Self,&str) {
+            let (rule_text,updates) = input.split_once("\n\n").unwrap_or((input,""));
+            let max_page = numbers(input).max().unwrap_or(0);
+            let mut rules = Self::new(max_page+1);
+            for line in rule_text.lines() {
+                let mut pair = numbers(line);
+                if let (Some(before),Some(after)) = (pair.next(),pair.next()) {
+                    rules.insert(before, after);
+                }
+            }
+            (rules,updates)
+        }
+
+        pub fn pages(&self) -> usize {
+            self.pages
+        }
+
+        // number of u64 words in each row
+        pub fn words(&self) -> usize {
+            self.words
+        }
+
+        // records the rule before|after
+        pub fn insert(&mut self, before: usize, after: usize) {
+            self.bits[before*self.words + after/64] |= 1<<(after%64);
+        }
+
+        pub fn contains(&self, before: usize, after: usize) -> bool {
+            self.row(before)[after/64] & (1<<(after%64)) != 0
+        }
+
+        pub fn row(&self, page: usize) -> &[u64] {
+            &self.bits[page*self.words..][..self.words]
+        }
+
+        // an empty page set the same width as a row
+        pub fn empty_set(&self) -> Vec<u64> {
+            vec![0;self.words]
+        }
+
+        // checks an update is in order, using seen as scratch
+        pub fn is_ordered(&self, line: &[usize], seen: &mut [u64]) -> bool {
+            seen.fill(0);
+            for &n in line {
+                if intersects(self.row(n), seen) {
+                    return false;
+                }
+                set_bit(seen, n);
+            }
+            true
+        }
+
+        // finds the page that lands in the middle once the update is reordered,
+        // same popcount trick as the fast path
+        pub fn middle_of_fixed(&self, line: &[usize], full: &mut [u64]) -> Option<usize> {
+            let mid_i = line.len()/2;
+            full.fill(0);
+            for &n in line {
+                set_bit(full, n);
+            }
+            line.iter().copied().find(|&n| count_and(full, self.row(n)) as usize == mid_i)
+        }
+    }
+
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    pub enum OrderError {
+        // these pages form a cycle, each one must come before the next
+        Cycle(Vec<usize>),
+        // no rule places these two pages relative to each other
+        Ambiguous(usize,usize),
+    }
+
+    impl Rules {
+        // returns the update in the order the rules require.
+        // works from the back: the last page is the only one with no successors left
+        pub fn reorder(&self, line: &[usize]) -> Result<Vec<usize>,OrderError> {
+            let mut remaining = self.empty_set();
+            for &n in line {
+                set_bit(&mut remaining, n);
+            }
+            let mut left: Vec<usize> = line.to_vec();
+            let mut order = Vec::with_capacity(line.len());
+
+            while !left.is_empty() {
+                let mut sinks = left.iter().copied().filter(|&n| !intersects(self.row(n), &remaining));
+                let Some(last) = sinks.next() else {
+                    return Err(OrderError::Cycle(self.find_cycle(&left, &remaining)));
+                };
+                if let Some(other) = sinks.next() {
+                    return Err(OrderError::Ambiguous(other,last));
+                }
+                remaining[last/64] &= !(1<<(last%64));
+                left.retain(|&n| n != last);
+                order.push(last);
+            }
+            order.reverse();
+            Ok(order)
+        }
+
+        // every page left has a successor that is also left, so following them must loop
+        fn find_cycle(&self, left: &[usize], remaining: &[u64]) -> Vec<usize> {
+            let mut path = vec![left[0]];
+            loop {
+                let n = *path.last().unwrap();
+                let next = left.iter().copied().find(|&m| self.contains(n, m) && remaining[m/64] & (1<<(m%64)) != 0).unwrap();
+                if let Some(start) = path.iter().position(|&p| p == next) {
+                    return path.split_off(start);
+                }
+                path.push(next);
+            }
+        }
+    }
+
+    impl Rules {
+        // transitive closure, bitset warshall over the rows.
+        // afterwards a page's row holds every page it's required to come before
+        pub fn closure(&self) -> Rules {
+            let mut closure = self.clone();
+            let words = self.words;
+            let mut via = vec![0;words];
+            for k in 0..self.pages {
+                via.copy_from_slice(closure.row(k));
+                if via.iter().all(|&w| w == 0) {
+                    continue;
+                }
+                for row in closure.bits.chunks_exact_mut(words) {
+                    if row[k/64] & (1<<(k%64)) != 0 {
+                        for (a,b) in row.iter_mut().zip(&via) {
+                            *a |= b;
+                        }
+                    }
+                }
+            }
+            closure
+        }
+    }
+
+    // sanity checks for a whole rule set, answered from the transitive closure
+    pub struct Analysis {
+        closure: Rules,
+    }
+
+    impl Analysis {
+        pub fn new(rules: &Rules) -> Self {
+            Self{closure: rules.closure()}
+        }
+
+        // is page a required before page b, through any chain of rules
+        pub fn required_before(&self, a: usize, b: usize) -> bool {
+            a < self.closure.pages() && b < self.closure.pages() && self.closure.contains(a, b)
+        }
+
+        pub fn closure(&self) -> &Rules {
+            &self.closure
+        }
+
+        pub fn is_acyclic(&self) -> bool {
+            (0..self.closure.pages()).all(|n| !self.closure.contains(n, n))
+        }
+
+        // groups of pages that are all required before each other, smallest page first
+        pub fn cycles(&self) -> Vec<Vec<usize>> {
+            let pages = self.closure.pages();
+            let mut done = self.closure.empty_set();
+            let mut cycles = Vec::new();
+            for n in 0..pages {
+                if done[n/64] & (1<<(n%64)) != 0 || !self.closure.contains(n, n) {
+                    continue;
+                }
+                let group: Vec<usize> = (n..pages).filter(|&m| self.closure.contains(n, m) && self.closure.contains(m, n)).collect();
+                for &m in &group {
+                    set_bit(&mut done, m);
+                }
+                cycles.push(group);
+            }
+            cycles
+        }
+    }
+
+    pub fn analyze(input: &str) -> Analysis {
+        Analysis::new(&Rules::parse(input).0)
+    }
+
+    // every incorrectly ordered update, put in the right order
+    pub fn fixed_updates(input: &str) -> Vec<Result<Vec<usize>,OrderError>> {
+        let (rules,updates) = Rules::parse(input);
+        let mut seen = rules.empty_set();
+        let mut line = Vec::new();
+
+        let mut fixed = Vec::new();
+        for text in updates.lines() {
+            read_update(text, &mut line);
+            if !line.is_empty() && !rules.is_ordered(&line, &mut seen) {
+                fixed.push(rules.reorder(&line));
+            }
+        }
+        fixed
+    }
+
+    pub(crate) fn set_bit(set: &mut [u64], n: usize) {
+        set[n/64] |= 1<<(n%64);
+    }
+
+    pub(crate) fn intersects(a: &[u64], b: &[u64]) -> bool {
+        a.iter().zip(b).any(|(a,b)| a & b != 0)
+    }
+
+    pub(crate) fn count_and(a: &[u64], b: &[u64]) -> u32 {
+        a.iter().zip(b).map(|(a,b)| (a & b).count_ones()).sum()
+    }
+
+    fn numbers(text: &str) -> impl Iterator<Item=usize> + '_ {
+        text.split(|c: char| !c.is_ascii_digit())
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse().unwrap())
+    }
+
+    fn read_update(text: &str, line: &mut Vec<usize>) {
+        line.clear();
+        line.extend(numbers(text));
+    }
+
+    fn general1(input: &str) -> i64 {
+        let (rules,updates) = Rules::parse(input);
+        let mut seen = rules.empty_set();
+        let mut line = Vec::new();
+
+        let mut sum = 0;
+        for text in updates.lines() {
+            read_update(text, &mut line);
+            if !line.is_empty() && rules.is_ordered(&line, &mut seen) {
+                sum += line[line.len()/2] as i64;
+            }
+        }
+        sum
+    }
+
+    fn general2(input: &str) -> i64 {
+        let (rules,updates) = Rules::parse(input);
+        let mut seen = rules.empty_set();
+        let mut line = Vec::new();
+
+        let mut sum = 0;
+        for text in updates.lines() {
+            read_update(text, &mut line);
+            if !line.is_empty() && !rules.is_ordered(&line, &mut seen) {
+                sum += rules.middle_of_fixed(&line, &mut seen).unwrap() as i64;
+            }
+        }
+        sum
+    }
 }
 
 
@@ -997,6 +1816,11 @@ pub mod day6 {
 
     pub fn part2(input: &str) -> i64 {
         //crate::benchmark("too",|| unsafe { impl2(input) })
+        impl2_jump(input)
+    }
+
+    // the original cell by cell walk, kept to check the jump tables against
+    pub fn part2_reference(input: &str) -> i64 {
         unsafe { impl2(input) }
     }
 
@@ -1317,15 +2141,19 @@ pub mod day6 {
         final_count()
     }
 
-    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
-    unsafe fn impl2(input: &str) -> i64 {
+    // copies the grid in and finds the guard
+    unsafe fn load(input: &str) -> (usize,usize) {
         let input = input.as_bytes();
         GRID.copy_from_slice(input);
-        
-        let mut looping_blockers = AHashSet::new();
 
         let start_index = GRID.iter().copied().position(|x| x == b'^').unwrap();
-        let start_pos = (start_index%131,start_index/131);
+        (start_index%131,start_index/131)
+    }
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl2(input: &str) -> i64 {
+        let start_pos = load(input);
+        let mut looping_blockers = AHashSet::new();
 
         let path = part2_base(start_pos);
         for path_point in path.iter() {
@@ -1347,49 +2175,376 @@ pub mod day6 {
 
         looping_blockers.len() as i64
     }
-}
 
-// did not participate in day 7
+    // jump tables for part2: for each cell and direction (N,E,S,W), the cell the guard stops in
+    // before the next obstacle. placing a candidate only rewrites the cells lined up behind it
+    const EXIT: u16 = u16::MAX;
 
-pub mod day8 {
-    use core::u8;
-    use std::simd::prelude::*;
+    fn cell((x, y): (usize,usize)) -> usize {
+        y*SIZE + x
+    }
 
-    use arrayvec::ArrayVec;
+    fn dir_index(dir: Direction) -> usize {
+        (dir as u8).trailing_zeros() as usize
+    }
 
-    const SIZE: usize = 50;
-    static mut TABLE: [ArrayVec<(i8,i8),4> ;256] = [
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
-        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+    fn step(pos: (usize,usize), dir: usize) -> Option<(usize,usize)> {
+        match dir {
+            0 => can_move_north(pos).then(|| move_north(pos)),
+            1 => can_move_east(pos).then(|| move_east(pos)),
+            2 => can_move_south(pos).then(|| move_south(pos)),
+            _ => can_move_west(pos).then(|| move_west(pos)),
+        }
+    }
+
+    // walk state for the jump table checks, one per thread. only reads the input grid
+    struct Jumps<'a> {
+        grid: &'a [u8],
+        jump: Vec<[u16;4]>,
+        // stamped with the candidate number instead of being cleared
+        turns: Vec<[u32;4]>,
+        stamp: u32,
+    }
+
+    impl<'a> Jumps<'a> {
+        fn new(grid: &'a [u8]) -> Self {
+            let mut jumps = Self{
+                grid,
+                jump: vec![[EXIT;4];SIZE*SIZE],
+                turns: vec![[0;4];SIZE*SIZE],
+                stamp: 0
+            };
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    if jumps.blocked((x,y)) {
+                        jumps.add_blocker((x,y));
+                    }
+                }
+            }
+            jumps
+        }
+
+        fn blocked(&self, (x, y): (usize,usize)) -> bool {
+            self.grid[y*(SIZE+1) + x] == b'#'
+        }
+
+        // sets the jump for dir on every free cell from start backwards, up to the previous obstacle
+        fn fill_behind(&mut self, start: (usize,usize), dir: usize, target: u16) {
+            let mut pos = start;
+            while !self.blocked(pos) {
+                self.jump[cell(pos)][dir] = target;
+                match step(pos, dir^2) {
+                    Some(next) => pos = next,
+                    None => break
+                }
+            }
+        }
+
+        // points everything lined up behind pos at it, returns the old targets for removal
+        fn add_blocker(&mut self, pos: (usize,usize)) -> [u16;4] {
+            let mut old = [EXIT;4];
+            for (dir,old) in old.iter_mut().enumerate() {
+                if let Some(back) = step(pos, dir^2) {
+                    if !self.blocked(back) {
+                        *old = self.jump[cell(back)][dir];
+                        self.fill_behind(back, dir, cell(back) as u16);
+                    }
+                }
+            }
+            old
+        }
+
+        fn remove_blocker(&mut self, pos: (usize,usize), old: [u16;4]) {
+            for (dir,old) in old.into_iter().enumerate() {
+                if let Some(back) = step(pos, dir^2) {
+                    self.fill_behind(back, dir, old);
+                }
+            }
+        }
+
+        // only visits the cells where the guard turns, a repeated turn means a loop
+        fn loops(&mut self, (pos,dir): ((usize,usize),Direction)) -> bool {
+            self.stamp += 1;
+            let mut at = cell(pos);
+            let mut dir = dir_index(dir);
+            loop {
+                let stop = self.jump[at][dir];
+                if stop == EXIT {
+                    return false;
+                }
+                at = stop as usize;
+                dir = (dir+1)&3;
+                if self.turns[at][dir] == self.stamp {
+                    return true;
+                }
+                self.turns[at][dir] = self.stamp;
+            }
+        }
+
+        fn loops_with(&mut self, (point,block_pos): Candidate) -> bool {
+            let old = self.add_blocker(block_pos);
+            let looped = self.loops(point);
+            self.remove_blocker(block_pos, old);
+            looped
+        }
+    }
+
+    // an obstruction to try, with the path point it's first run into from
+    type Candidate = (((usize,usize),Direction),(usize,usize));
+
+    const DIRECTIONS: [Direction;4] = [Direction::North,Direction::East,Direction::South,Direction::West];
+
+    // every candidate in path order. a cell the guard already walked through can't be blocked,
+    // so each one only shows up once. walks its own copy of the state instead of GRID,
+    // so the threaded part2 can be called from anywhere
+    fn candidates(input: &str) -> Vec<Candidate> {
+        let grid = input.as_bytes();
+        let blocked = |(x, y): (usize,usize)| grid[y*(SIZE+1) + x] == b'#';
+        let mut walked = vec![false;SIZE*SIZE];
+        let mut result = Vec::new();
+
+        let start_index = grid.iter().position(|&x| x == b'^').unwrap();
+        let start_pos = (start_index%(SIZE+1),start_index/(SIZE+1));
+        let (mut pos,mut dir) = (start_pos,0);
+        loop {
+            let next = step(pos, dir);
+            if let Some(block_pos) = next {
+                if !blocked(block_pos) && start_pos != block_pos && !walked[cell(block_pos)] {
+                    result.push(((pos,DIRECTIONS[dir]),block_pos));
+                }
+            }
+            // disallow blocking already walked paths
+            walked[cell(pos)] = true;
+
+            match next {
+                None => break,
+                Some(next) if blocked(next) => dir = (dir+1)&3,
+                Some(next) => pos = next
+            }
+        }
+        result
+    }
+
+    fn impl2_jump(input: &str) -> i64 {
+        looping_obstructions(input).len() as i64
+    }
+
+    // cells where one extra obstruction traps the guard in a loop, in the order the path reaches them
+    pub fn looping_obstructions(input: &str) -> Vec<(usize,usize)> {
+        let candidates = candidates(input);
+        let mut jumps = Jumps::new(input.as_bytes());
+        candidates.into_iter().filter(|&c| jumps.loops_with(c)).map(|(_,pos)| pos).collect()
+    }
+
+    // part2 spread over every core
+    pub fn part2_parallel(input: &str) -> i64 {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        part2_threads(input, threads)
+    }
+
+    // part2 split over a fixed number of threads, each with its own jump tables
+    pub fn part2_threads(input: &str, threads: usize) -> i64 {
+        let candidates = candidates(input);
+        let chunk_size = candidates.len().div_ceil(threads.max(1)).max(1);
+        let grid = input.as_bytes();
+
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = candidates.chunks(chunk_size).map(|chunk| {
+                scope.spawn(move || {
+                    let mut jumps = Jumps::new(grid);
+                    chunk.iter().filter(|&&c| jumps.loops_with(c)).count()
+                })
+            }).collect();
+            // joined in chunk order, so the sum is the same however the threads get scheduled
+            workers.into_iter().map(|w| w.join().unwrap()).sum::<usize>() as i64
+        })
+    }
+
+    // picture of the guard's route, each cell is a set of the flags on this type
+    pub struct Render {
+        pub cells: Vec<u8>,
+        // the route ends in a loop instead of leaving the map
+        pub looped: bool,
+    }
+
+    impl Render {
+        pub const OBSTACLE: u8 = 1;
+        pub const VERTICAL: u8 = 2;
+        pub const HORIZONTAL: u8 = 4;
+        pub const TURN: u8 = 8;
+        pub const START: u8 = 16;
+        // an obstruction that causes a loop
+        pub const BLOCKER: u8 = 32;
+
+        pub const WIDTH: usize = SIZE;
+        pub const HEIGHT: usize = SIZE;
+
+        // the part1 route
+        pub fn path(input: &str) -> Self {
+            Self::walk(input.as_bytes(), None)
+        }
+
+        // the route with one extra obstruction, for looking at a single part2 loop
+        pub fn with_obstruction(input: &str, pos: (usize,usize)) -> Self {
+            let mut render = Self::walk(input.as_bytes(), Some(pos));
+            render.cells[cell(pos)] |= Self::BLOCKER;
+            render
+        }
+
+        // overlays obstruction candidates, usually from looping_obstructions
+        pub fn mark_obstructions(&mut self, obstructions: &[(usize,usize)]) {
+            for &pos in obstructions {
+                self.cells[cell(pos)] |= Self::BLOCKER;
+            }
+        }
+
+        // cell by cell, like part1, recording directions and turns
+        fn walk(grid: &[u8], extra: Option<(usize,usize)>) -> Self {
+            let blocked = |(x, y): (usize,usize)| grid[y*(SIZE+1) + x] == b'#' || Some((x,y)) == extra;
+
+            let mut cells: Vec<u8> = (0..SIZE*SIZE).map(|i| {
+                if blocked((i%SIZE,i/SIZE)) { Self::OBSTACLE } else { 0 }
+            }).collect();
+            let mut walked = vec![0u8;SIZE*SIZE];
+
+            let start_index = grid.iter().position(|&x| x == b'^').unwrap();
+            let mut pos = (start_index%(SIZE+1),start_index/(SIZE+1));
+            let mut dir = 0;
+            cells[cell(pos)] |= Self::START;
+
+            let looped = loop {
+                let i = cell(pos);
+                if walked[i] & (1<<dir) != 0 {
+                    break true;
+                }
+                walked[i] |= 1<<dir;
+                cells[i] |= if dir & 1 == 0 { Self::VERTICAL } else { Self::HORIZONTAL };
+
+                match step(pos, dir) {
+                    None => break false,
+                    Some(next) if blocked(next) => {
+                        cells[i] |= Self::TURN;
+                        dir = (dir+1)&3;
+                    }
+                    Some(next) => pos = next
+                }
+            };
+            Self{cells,looped}
+        }
+
+        // glyph, ansi colour, rgb and grey level for a cell, most important flag wins
+        fn style(flags: u8) -> (char,&'static str,[u8;3],u8) {
+            if flags & Self::START != 0 {
+                ('^',"1;32",[60,200,90],255)
+            } else if flags & Self::BLOCKER != 0 {
+                ('O',"1;31",[230,50,50],224)
+            } else if flags & Self::OBSTACLE != 0 {
+                ('#',"37",[190,190,190],192)
+            } else if flags & Self::TURN != 0 || flags & (Self::VERTICAL|Self::HORIZONTAL) == Self::VERTICAL|Self::HORIZONTAL {
+                ('+',"33",[240,200,60],160)
+            } else if flags & Self::VERTICAL != 0 {
+                ('|',"34",[60,110,220],112)
+            } else if flags & Self::HORIZONTAL != 0 {
+                ('-',"34",[60,110,220],112)
+            } else {
+                ('.',"90",[24,24,24],0)
+            }
+        }
+
+        // text version, with ansi colours if asked
+        pub fn to_terminal(&self, colour: bool) -> String {
+            let mut out = String::with_capacity(SIZE*(SIZE+1)*if colour { 8 } else { 1 });
+            for row in self.cells.chunks_exact(SIZE) {
+                let mut last = "";
+                for &flags in row {
+                    let (glyph,ansi,..) = Self::style(flags);
+                    if colour && ansi != last {
+                        out.push_str("\x1b[");
+                        out.push_str(ansi);
+                        out.push('m');
+                        last = ansi;
+                    }
+                    out.push(glyph);
+                }
+                if colour {
+                    out.push_str("\x1b[0m");
+                }
+                out.push('\n');
+            }
+            out
+        }
+
+        // binary ppm (P6), each cell drawn as a scale by scale square
+        pub fn write_ppm(&self, out: &mut impl std::io::Write, scale: usize) -> std::io::Result<()> {
+            self.write_image(out, scale, "P6", |flags,pixel| pixel.extend(Self::style(flags).2))
+        }
+
+        // binary pgm (P5), same layout as the ppm
+        pub fn write_pgm(&self, out: &mut impl std::io::Write, scale: usize) -> std::io::Result<()> {
+            self.write_image(out, scale, "P5", |flags,pixel| pixel.push(Self::style(flags).3))
+        }
+
+        fn write_image(&self, out: &mut impl std::io::Write, scale: usize, magic: &str, pixel: impl Fn(u8,&mut Vec<u8>)) -> std::io::Result<()> {
+            let scale = scale.max(1);
+            write!(out, "{}\n{} {}\n255\n", magic, SIZE*scale, SIZE*scale)?;
+            let mut line = Vec::new();
+            for row in self.cells.chunks_exact(SIZE) {
+                line.clear();
+                for &flags in row {
+                    for _ in 0..scale {
+                        pixel(flags, &mut line);
+                    }
+                }
+                for _ in 0..scale {
+                    out.write_all(&line)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// did not participate in day 7
+
+pub mod day8 {
+    use core::u8;
+    use std::simd::prelude::*;
+
+    use arrayvec::ArrayVec;
+
+    const SIZE: usize = 50;
+    static mut TABLE: [ArrayVec<(i8,i8),4> ;256] = [
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
+        ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
         ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
         ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
         ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),ArrayVec::new_const(),
@@ -1602,15 +2757,29 @@ pub mod day8 {
 
 pub mod day9 {
     use core::{iter::Iterator, u16, usize};
+    use std::{cmp::Reverse, collections::BinaryHeap};
 
     pub fn part1(input: &str) -> i64 {
-        unsafe { impl1(input) }
+        narrow_checksum(unsafe { impl1(input) })
     }
 
     pub fn part2(input: &str) -> i64 {
+        narrow_checksum(unsafe { impl2(input) })
+    }
+
+    // checksums of disk maps with millions of digits don't fit an i64
+    pub fn part1_wide(input: &str) -> u128 {
+        unsafe { impl1(input) }
+    }
+
+    pub fn part2_wide(input: &str) -> u128 {
         unsafe { impl2(input) }
     }
 
+    fn narrow_checksum(sum: u128) -> i64 {
+        i64::try_from(sum).expect("checksum overflows i64, use the _wide version")
+    }
+
     #[derive(Debug,Clone,Copy)]
     struct File {
         id: u32,
@@ -1623,7 +2792,9 @@ pub mod day9 {
         extra_back: File,
         input: &'a [u8],
         next_disk_index: usize,
-        sum: i64
+        sum: u128,
+        // only filled in when asking for the layout
+        runs: Option<Vec<Run>>
     }
 
     impl<'a> Part1<'a> {
@@ -1642,7 +2813,8 @@ pub mod day9 {
                 extra_back: File{id: 0, size: 0},
                 input,
                 next_disk_index: 0,
-                sum: 0
+                sum: 0,
+                runs: None
             }
         }
 
@@ -1734,61 +2906,106 @@ pub mod day9 {
         }
     
         fn tally(&mut self, file: File) {
+            if let Some(runs) = &mut self.runs {
+                push_run(runs, Some(file.id), file.size as u64);
+            }
             for _ in 0..file.size {
-                self.sum += file.id as i64 * self.next_disk_index as i64;
+                self.sum += file.id as u128 * self.next_disk_index as u128;
                 self.next_disk_index += 1;
             }
         }
     }
 
     #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
-    unsafe fn impl1(input: &str) -> i64 {
+    unsafe fn impl1(input: &str) -> u128 {
         let mut part1 = Part1::new(input);
         while part1.step() {}
         part1.sum
     }
 
+    // the compacted disk after part1, block by block
+    pub fn layout1(input: &str) -> Layout {
+        let mut part1 = Part1::new(input);
+        part1.runs = Some(Vec::new());
+        while part1.step() {}
+        let mut runs = part1.runs.unwrap();
+        let free = disk_size(input) - part1.next_disk_index as u64;
+        push_run(&mut runs, None, free);
+        Layout{runs}
+    }
+
+    // index width for ids and gap links, u16 while everything fits since it packs tighter
+    trait Link: Copy + Eq + std::fmt::Debug {
+        const NONE: Self;
+        fn from_index(index: usize) -> Self;
+        fn index(self) -> usize;
+    }
+
+    impl Link for u16 {
+        const NONE: Self = u16::MAX;
+        fn from_index(index: usize) -> Self { index as u16 }
+        fn index(self) -> usize { self as usize }
+    }
+
+    impl Link for u32 {
+        const NONE: Self = u32::MAX;
+        fn from_index(index: usize) -> Self { index as u32 }
+        fn index(self) -> usize { self as usize }
+    }
+
+    // every file and every gap gets an index, NONE has to stay out of reach
+    fn is_narrow(input: &str) -> bool {
+        input.len()/2 < u16::MAX as usize
+    }
+
     #[derive(Debug)]
-    struct File2 {
-        id: u16,
+    struct File2<I> {
+        id: I,
         size: u8,
         offset: u32
     }
 
     #[derive(Debug)]
-    struct Gap2 {
+    struct Gap2<I> {
         size: u8,
         offset: u32,
-        next: u16
+        next: I
     }
 
-    struct GapFinder {
-        gaps: Vec<Gap2>,
-        chain_starts: [u16;10],
-        chain_ends: [u16;10]
+    // where part2 looks for space, so the chains can be compared against other structures
+    trait FindGap {
+        fn add(&mut self, size: u8, offset: u32);
+        // takes size blocks from the leftmost gap that fits, as long as it's before max_offset
+        fn next_gap(&mut self, size: u8, max_offset: u32) -> Option<u32>;
     }
 
-    impl GapFinder {
-        fn new() -> Self {
+    struct GapFinder<I> {
+        gaps: Vec<Gap2<I>>,
+        chain_starts: [I;10],
+        chain_ends: [I;10]
+    }
+
+    impl<I: Link> GapFinder<I> {
+        fn new(capacity: usize) -> Self {
             Self {
-                gaps: Vec::<Gap2>::with_capacity(11000),
-                chain_starts: [u16::MAX;10],
-                chain_ends: [u16::MAX;10],
+                gaps: Vec::<Gap2<I>>::with_capacity(capacity),
+                chain_starts: [I::NONE;10],
+                chain_ends: [I::NONE;10],
             }
         }
 
-        fn push(&mut self, gap: Gap2) {
+        fn push(&mut self, gap: Gap2<I>) {
             let new_index = self.gaps.len();
             // set chain start if no chain
-            if self.chain_starts[gap.size as usize] == u16::MAX {
-                self.chain_starts[gap.size as usize] = new_index as u16;
+            if self.chain_starts[gap.size as usize] == I::NONE {
+                self.chain_starts[gap.size as usize] = I::from_index(new_index);
             }
             // link chain end
-            if self.chain_ends[gap.size as usize] != u16::MAX {
-                let end_index = self.chain_ends[gap.size as usize] as usize;
-                self.gaps[end_index].next = new_index as u16;
+            if self.chain_ends[gap.size as usize] != I::NONE {
+                let end_index = self.chain_ends[gap.size as usize].index();
+                self.gaps[end_index].next = I::from_index(new_index);
             }
-            self.chain_ends[gap.size as usize] = new_index as u16;
+            self.chain_ends[gap.size as usize] = I::from_index(new_index);
 
             self.gaps.push(gap);
         }
@@ -1806,31 +3023,31 @@ pub mod day9 {
             let insert_offset = gap.offset;
 
             let next_index = self.chain_starts[gap.size as usize];
-            if next_index == u16::MAX {
+            if next_index == I::NONE {
                 // list is empty
-                self.chain_starts[gap.size as usize] = index as u16;
+                self.chain_starts[gap.size as usize] = I::from_index(index);
                 self.gaps[index].next = next_index;
             } else {
-                let check_gap = &self.gaps[next_index as usize];
+                let check_gap = &self.gaps[next_index.index()];
     
                 if insert_offset < check_gap.offset {
                     // insert first
-                    self.chain_starts[gap.size as usize] = index as u16;
+                    self.chain_starts[gap.size as usize] = I::from_index(index);
                     self.gaps[index].next = next_index;
                 } else {
                     let mut current_index = next_index;
                     loop {
-                        let next_index = self.gaps[current_index as usize].next;
-                        if next_index == u16::MAX {
+                        let next_index = self.gaps[current_index.index()].next;
+                        if next_index == I::NONE {
                             // end of list
-                            self.gaps[current_index as usize].next = index as u16;
+                            self.gaps[current_index.index()].next = I::from_index(index);
                             self.gaps[index].next = next_index;
                             break;
                         } else {
-                            let check_gap = &self.gaps[next_index as usize];
+                            let check_gap = &self.gaps[next_index.index()];
                             if insert_offset < check_gap.offset {
                                 // insert
-                                self.gaps[current_index as usize].next = index as u16;
+                                self.gaps[current_index.index()].next = I::from_index(index);
                                 self.gaps[index].next = next_index;
                                 break;
                             }
@@ -1841,14 +3058,25 @@ pub mod day9 {
             }
         }
 
+    }
+
+    impl<I: Link> FindGap for GapFinder<I> {
+        fn add(&mut self, size: u8, offset: u32) {
+            self.push(Gap2{
+                size,
+                offset,
+                next: I::NONE
+            });
+        }
+
         fn next_gap(&mut self, size: u8, max_offset: u32) -> Option<u32> {
 
             // find gap
             let mut first_index = usize::MAX;
             for check_size in size..10 {
-                let index = self.chain_starts[check_size as usize] as usize;
-                if index != u16::MAX as usize {
-                    first_index = first_index.min(index);
+                let index = self.chain_starts[check_size as usize];
+                if index != I::NONE {
+                    first_index = first_index.min(index.index());
                 }
             }
 
@@ -1883,21 +3111,83 @@ pub mod day9 {
         }
     }
 
-    fn tally2(file: &File2) -> i64 {
+    // the same search as a min-heap of offsets per gap size, log n where the chains can walk
+    struct HeapGapFinder {
+        heaps: [BinaryHeap<Reverse<u32>>;10]
+    }
+
+    impl HeapGapFinder {
+        fn new() -> Self {
+            Self{heaps: Default::default()}
+        }
+    }
+
+    impl FindGap for HeapGapFinder {
+        fn add(&mut self, size: u8, offset: u32) {
+            self.heaps[size as usize].push(Reverse(offset));
+        }
+
+        fn next_gap(&mut self, size: u8, max_offset: u32) -> Option<u32> {
+            let (offset,gap_size) = (size..10)
+                .filter_map(|gap_size| self.heaps[gap_size as usize].peek().map(|&Reverse(offset)| (offset,gap_size)))
+                .min()?;
+            if offset > max_offset {
+                return None;
+            }
+            self.heaps[gap_size as usize].pop();
+            if gap_size > size {
+                self.add(gap_size - size, offset + size as u32);
+            }
+            Some(offset)
+        }
+    }
+
+    fn tally2<I: Link>(file: &File2<I>) -> u128 {
         let mut sum = 0;
         let mut disk_index = file.offset;
         for _ in 0..file.size {
-            sum += file.id as i64 * disk_index as i64;
+            sum += file.id.index() as u128 * disk_index as u128;
             disk_index += 1;
         }
         sum
     }
 
     #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
-    unsafe fn impl2(input: &str) -> i64 {
+    unsafe fn impl2(input: &str) -> u128 {
+        if is_narrow(input) {
+            compact2::<u16>(input, GapFinder::<u16>::new(input.len()/2)).iter().map(tally2).sum()
+        } else {
+            compact2::<u32>(input, GapFinder::<u32>::new(input.len()/2)).iter().map(tally2).sum()
+        }
+    }
+
+    // the compacted disk after part2, whole files only
+    pub fn layout2(input: &str) -> Layout {
+        if is_narrow(input) {
+            layout2_from::<u16>(input, compact2(input, GapFinder::<u16>::new(input.len()/2)))
+        } else {
+            layout2_from::<u32>(input, compact2(input, GapFinder::<u32>::new(input.len()/2)))
+        }
+    }
+
+    fn layout2_from<I: Link>(input: &str, mut files: Vec<File2<I>>) -> Layout {
+        files.sort_unstable_by_key(|file| file.offset);
+
+        let mut runs = Vec::new();
+        let mut disk_index = 0;
+        for file in files {
+            push_run(&mut runs, None, (file.offset - disk_index) as u64);
+            push_run(&mut runs, Some(file.id.index() as u32), file.size as u64);
+            disk_index = file.offset + file.size as u32;
+        }
+        push_run(&mut runs, None, disk_size(input) - disk_index as u64);
+        Layout{runs}
+    }
+
+    // every file in its final place, still in id order
+    fn compact2<I: Link>(input: &str, mut gaps: impl FindGap) -> Vec<File2<I>> {
+        let mut files = Vec::<File2<I>>::with_capacity(input.len()/2 + 1);
         let mut input = input.bytes();
-        let mut files = Vec::<File2>::with_capacity(11000);
-        let mut gaps = GapFinder::new();
         // parse
         {
             let mut next_id = 0;
@@ -1909,7 +3199,7 @@ pub mod day9 {
                     if b == b'\n' { break };
                     let size = b - b'0';
                     files.push(File2{
-                        id: next_id,
+                        id: I::from_index(next_id),
                         size,
                         offset: next_offset
                     });
@@ -1922,28 +3212,128 @@ pub mod day9 {
                     if b == b'\n' { break };
                     let size = b - b'0';
                     if size > 0 {
-                        gaps.push(Gap2{
-                            size,
-                            offset: next_offset,
-                            next: u16::MAX
-                        });
+                        gaps.add(size, next_offset);
                     }
                     next_offset += size as u32;
                 }
             }
         }
         // process
-        {
+        for file in files.iter_mut().rev() {
+            if let Some(new_offset) = gaps.next_gap(file.size, file.offset) {
+                file.offset = new_offset;
+            }
+        }
+        files
+    }
+
+    // times part2 on a random disk map with the given number of digits, with the linked
+    // chains against the min-heap finder. returns both checksums, which should match
+    pub fn bench_gap_finders(digits: usize, seed: u64) -> (u128,u128) {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut input: String = (0..digits).map(|i| {
+            // files are never empty
+            let low = if i % 2 == 0 { 1 } else { 0 };
+            (b'0' + rng.gen_range(low..10)) as char
+        }).collect();
+        input.push('\n');
+
+        let chains = crate::benchmark("day9 chains", || {
+            compact2::<u32>(&input, GapFinder::<u32>::new(digits/2)).iter().map(tally2).sum()
+        });
+        let heaps = crate::benchmark("day9 heaps", || {
+            compact2::<u32>(&input, HeapGapFinder::new()).iter().map(tally2).sum()
+        });
+        (chains,heaps)
+    }
+
+    fn disk_size(input: &str) -> u64 {
+        input.trim_end().bytes().map(|b| (b - b'0') as u64).sum()
+    }
+
+    // layouts up to this many blocks print in the puzzle's own form
+    pub const SMALL_LAYOUT: u64 = 1 << 12;
+
+    // a stretch of blocks on the disk, free space has no id
+    #[derive(Debug,Clone,Copy,PartialEq,Eq)]
+    pub struct Run {
+        pub id: Option<u32>,
+        pub len: u64
+    }
+
+    // merges with the previous run when it's the same file or more free space
+    fn push_run(runs: &mut Vec<Run>, id: Option<u32>, len: u64) {
+        if len == 0 {
+            return;
+        }
+        match runs.last_mut() {
+            Some(last) if last.id == id => last.len += len,
+            _ => runs.push(Run{id,len})
+        }
+    }
+
+    // a whole compacted disk, for diffing against a straightforward reference
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    pub struct Layout {
+        pub runs: Vec<Run>
+    }
+
+    impl Layout {
+        pub fn blocks(&self) -> u64 {
+            self.runs.iter().map(|run| run.len).sum()
+        }
+
+        pub fn checksum(&self) -> u128 {
             let mut sum = 0;
-            for file_i in (0..files.len()).rev() {
-                let file = &mut files[file_i];
-                if let Some(new_offset) = gaps.next_gap(file.size, file.offset) {
-                    file.offset = new_offset;
+            let mut disk_index = 0;
+            for run in &self.runs {
+                if let Some(id) = run.id {
+                    // sum of disk_index..disk_index+len
+                    let span = run.len * (2*disk_index + run.len - 1) / 2;
+                    sum += id as u128 * span as u128;
                 }
-                sum += tally2(file);
+                disk_index += run.len;
             }
             sum
         }
+
+        // the puzzle's form like 0099811188827773336446555566.., only possible while ids fit in one digit
+        pub fn to_block_string(&self) -> Option<String> {
+            let mut out = String::with_capacity(self.blocks() as usize);
+            for run in &self.runs {
+                let c = match run.id {
+                    Some(id) if id < 10 => (b'0' + id as u8) as char,
+                    Some(_) => return None,
+                    None => '.'
+                };
+                out.extend(std::iter::repeat_n(c, run.len as usize));
+            }
+            Some(out)
+        }
+
+        // one "id len" line per run, with "." as the id of free space
+        pub fn to_run_string(&self) -> String {
+            use std::fmt::Write;
+            let mut out = String::new();
+            for run in &self.runs {
+                match run.id {
+                    Some(id) => writeln!(out, "{} {}", id, run.len).unwrap(),
+                    None => writeln!(out, ". {}", run.len).unwrap()
+                }
+            }
+            out
+        }
+    }
+
+    // block form when it's small enough to read, runs otherwise
+    impl std::fmt::Display for Layout {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self.to_block_string() {
+                Some(blocks) if self.blocks() <= SMALL_LAYOUT => f.write_str(&blocks),
+                _ => f.write_str(&self.to_run_string())
+            }
+        }
     }
 }
 
@@ -1952,33 +3342,275 @@ pub mod day10 {
 
     const SIZE: usize = 45;
 
+    // the asm solvers only handle the real 45x45 input
     pub fn part1(input: &str) -> i64 {
-        unsafe { impl1_turbocursed(input) }
+        if Grid::new(input).is_size(SIZE) {
+            unsafe { impl1_turbocursed(input) }
+        } else {
+            part1_safe(input)
+        }
     }
 
     pub fn part2(input: &str) -> i64 {
-        unsafe { impl2_turbocursed(input) }
+        if Grid::new(input).is_size(SIZE) {
+            unsafe { impl2_turbocursed(input) }
+        } else {
+            part2_safe(input)
+        }
     }
-    
-    static mut MAP: [u16;SIZE*SIZE] = [0;SIZE*SIZE];
 
-    unsafe fn impl1_turbocursed(input: &str) -> i64 {
-        let input = input.as_bytes();
+    // scores for any grid size without asm
+    pub fn part1_safe(input: &str) -> i64 {
+        let grid = Grid::new(input);
+        let mut search = Bitsets::new(&grid);
+        grid.trailheads().map(|start| search.score(grid.index(start)) as i64).sum()
+    }
 
-        MAP.fill(0);
+    // ratings for any grid size without asm
+    pub fn part2_safe(input: &str) -> i64 {
+        let grid = Grid::new(input);
+        let ways = grid.ratings();
+        grid.trailheads().map(|start| ways[grid.index(start)] as i64).sum()
+    }
 
-        unsafe fn count(input: &[u8], b: u8, (mut x,mut y): (usize,usize), tag: u16) -> i64 {
-            use std::arch::asm;
-            let mut sum: i64 = 0;
-            let mut b = b as i16;
-            let mut input_index = input.as_ptr() as usize + y * (SIZE+1) + x;
-            let mut map_index = MAP.as_ptr() as usize + (y * SIZE + x) * 2;
+    // a trail map of any size, rows of height digits ending in newlines
+    pub struct Grid<'a> {
+        bytes: &'a [u8],
+        pub width: usize,
+        pub height: usize
+    }
 
-            asm!(
-                "call 2f",
-                "jmp 1002f",
+    impl<'a> Grid<'a> {
+        pub fn new(input: &'a str) -> Self {
+            let bytes = input.as_bytes();
+            let width = bytes.iter().position(|&b| b == b'\n').unwrap_or(bytes.len());
+            // the last newline is optional
+            let height = (bytes.len()+1)/(width+1);
+            Self{bytes,width,height}
+        }
 
-                "2:",
+        fn is_size(&self, size: usize) -> bool {
+            self.width == size && self.height == size
+        }
+
+        // height at a cell, anything that isn't a digit comes out above 9
+        pub fn at(&self, (x, y): (usize,usize)) -> u8 {
+            self.bytes[y*(self.width+1) + x].wrapping_sub(b'0')
+        }
+
+        fn index(&self, (x, y): (usize,usize)) -> usize {
+            y*self.width + x
+        }
+
+        fn cells(&self) -> impl Iterator<Item=(usize,usize)> + '_ {
+            (0..self.height).flat_map(move |y| (0..self.width).map(move |x| (x,y)))
+        }
+
+        pub fn trailheads(&self) -> impl Iterator<Item=(usize,usize)> + '_ {
+            self.cells().filter(|&pos| self.at(pos) == 0)
+        }
+
+        // neighbours one step higher
+        fn uphill(&self, (x, y): (usize,usize)) -> impl Iterator<Item=(usize,usize)> + '_ {
+            let want = self.at((x,y)) + 1;
+            let steps = [
+                (x+1 < self.width).then(|| (x+1,y)),
+                (x > 0).then(|| (x-1,y)),
+                (y+1 < self.height).then(|| (x,y+1)),
+                (y > 0).then(|| (x,y-1))
+            ];
+            steps.into_iter().flatten().filter(move |&pos| self.at(pos) == want)
+        }
+
+        // distinct trails from each cell to any 9, counted from the top down
+        pub fn ratings(&self) -> Vec<u64> {
+            let mut ways = vec![0u64;self.width*self.height];
+            let mut by_height: [Vec<(usize,usize)>;10] = Default::default();
+            for pos in self.cells() {
+                if let Some(list) = by_height.get_mut(self.at(pos) as usize) {
+                    list.push(pos);
+                }
+            }
+            for &pos in &by_height[9] {
+                ways[self.index(pos)] = 1;
+            }
+            for h in (0..9).rev() {
+                for &pos in &by_height[h] {
+                    ways[self.index(pos)] = self.uphill(pos).map(|next| ways[self.index(next)]).sum();
+                }
+            }
+            ways
+        }
+    }
+
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    pub struct Trailhead {
+        pub pos: (usize,usize),
+        // distinct 9s it reaches
+        pub score: u32,
+        // distinct trails it starts
+        pub rating: u64
+    }
+
+    // every trailhead in reading order, with its own score and rating
+    pub fn trailheads(input: &str) -> Vec<Trailhead> {
+        let grid = Grid::new(input);
+        let ways = grid.ratings();
+        let mut tags = vec![0u32;grid.width*grid.height];
+        grid.trailheads().zip(1..).map(|(pos,tag)| Trailhead{
+            pos,
+            score: grid.tagged_score(&mut tags, pos, tag),
+            rating: ways[grid.index(pos)]
+        }).collect()
+    }
+
+    // a whole trail, from the 0 to the 9
+    pub type Trail = [(usize,usize);10];
+
+    // the trails starting at pos, or None once there are more than max_trails of them
+    pub fn trails(input: &str, pos: (usize,usize), max_trails: usize) -> Option<Vec<Trail>> {
+        let grid = Grid::new(input);
+        let mut trails = Vec::new();
+        let mut trail = [pos;10];
+        if grid.at(pos) == 0 && !grid.collect_trails(&mut trail, 0, &mut trails, max_trails) {
+            return None;
+        }
+        Some(trails)
+    }
+
+    impl Grid<'_> {
+        // same tagging as impl1_turbocursed: a cell holds the tag of the last trailhead that reached it,
+        // so the map never needs clearing between trailheads
+        fn tagged_score(&self, tags: &mut [u32], start: (usize,usize), tag: u32) -> u32 {
+            let mut score = 0;
+            let mut stack = vec![start];
+            tags[self.index(start)] = tag;
+            while let Some(pos) = stack.pop() {
+                if self.at(pos) == 9 {
+                    score += 1;
+                    continue;
+                }
+                for next in self.uphill(pos) {
+                    let i = self.index(next);
+                    if tags[i] != tag {
+                        tags[i] = tag;
+                        stack.push(next);
+                    }
+                }
+            }
+            score
+        }
+
+        // false once the limit is passed
+        fn collect_trails(&self, trail: &mut Trail, depth: usize, trails: &mut Vec<Trail>, max_trails: usize) -> bool {
+            if depth == 9 {
+                trails.push(*trail);
+                return trails.len() <= max_trails;
+            }
+            for next in self.uphill(trail[depth]) {
+                trail[depth+1] = next;
+                if !self.collect_trails(trail, depth+1, trails, max_trails) {
+                    return false;
+                }
+            }
+            true
+        }
+    }
+
+    // part1 as a bitset bfs, spreading the whole frontier one height at a time
+    struct Bitsets {
+        width: usize,
+        // bit y*width+x is set on cells of that height
+        levels: [Vec<u64>;10],
+        not_first_col: Vec<u64>,
+        not_last_col: Vec<u64>,
+        frontier: Vec<u64>,
+        next: Vec<u64>
+    }
+
+    impl Bitsets {
+        fn new(grid: &Grid) -> Self {
+            let words = (grid.width*grid.height).div_ceil(64);
+            let mut levels: [Vec<u64>;10] = Default::default();
+            for level in levels.iter_mut() {
+                *level = vec![0;words];
+            }
+            let mut not_first_col = vec![0;words];
+            let mut not_last_col = vec![0;words];
+            for pos in grid.cells() {
+                let i = grid.index(pos);
+                if let Some(level) = levels.get_mut(grid.at(pos) as usize) {
+                    level[i/64] |= 1<<(i%64);
+                }
+                if pos.0 != 0 {
+                    not_first_col[i/64] |= 1<<(i%64);
+                }
+                if pos.0 != grid.width-1 {
+                    not_last_col[i/64] |= 1<<(i%64);
+                }
+            }
+            Self{width: grid.width, levels, not_first_col, not_last_col, frontier: vec![0;words], next: vec![0;words]}
+        }
+
+        // word i of bits moved n places towards the end
+        #[inline(always)]
+        fn shifted(bits: &[u64], i: usize, n: isize) -> u64 {
+            let (words,shift) = (n.unsigned_abs()/64, n.unsigned_abs() as u32 % 64);
+            let word = |j: Option<usize>| j.and_then(|j| bits.get(j)).copied().unwrap_or(0);
+            if n >= 0 {
+                let (lo,carry) = (word(i.checked_sub(words)), word(i.checked_sub(words+1)));
+                if shift == 0 { lo } else { (lo << shift) | (carry >> (64-shift)) }
+            } else {
+                let (hi,carry) = (word(Some(i+words)), word(Some(i+words+1)));
+                if shift == 0 { hi } else { (hi >> shift) | (carry << (64-shift)) }
+            }
+        }
+
+        // number of 9s reachable from the start cell
+        fn score(&mut self, start: usize) -> u32 {
+            self.frontier.fill(0);
+            self.frontier[start/64] |= 1<<(start%64);
+            let width = self.width as isize;
+            for level in &self.levels[1..] {
+                let frontier = &self.frontier;
+                let mut any = 0;
+                for (i,next) in self.next.iter_mut().enumerate() {
+                    let spread =
+                        (Self::shifted(frontier, i, 1) & self.not_first_col[i]) |
+                        (Self::shifted(frontier, i, -1) & self.not_last_col[i]) |
+                        Self::shifted(frontier, i, width) |
+                        Self::shifted(frontier, i, -width);
+                    *next = spread & level[i];
+                    any |= *next;
+                }
+                if any == 0 {
+                    return 0;
+                }
+                std::mem::swap(&mut self.frontier, &mut self.next);
+            }
+            self.frontier.iter().map(|w| w.count_ones()).sum()
+        }
+    }
+    
+    static mut MAP: [u16;SIZE*SIZE] = [0;SIZE*SIZE];
+
+    unsafe fn impl1_turbocursed(input: &str) -> i64 {
+        let input = input.as_bytes();
+
+        MAP.fill(0);
+
+        unsafe fn count(input: &[u8], b: u8, (mut x,mut y): (usize,usize), tag: u16) -> i64 {
+            use std::arch::asm;
+            let mut sum: i64 = 0;
+            let mut b = b as i16;
+            let mut input_index = input.as_ptr() as usize + y * (SIZE+1) + x;
+            let mut map_index = MAP.as_ptr() as usize + (y * SIZE + x) * 2;
+
+            asm!(
+                "call 2f",
+                "jmp 1002f",
+
+                "2:",
                 "mov word ptr [{map_index}], {tag:x}",
                 // 9 check
                 "cmp {b:x},0x39",
@@ -2217,6 +3849,9 @@ pub mod day10 {
 }
 
 pub mod day11 {
+    use std::num::IntErrorKind;
+
+    use ahash::AHashMap;
     use arrayvec::ArrayVec;
 
     static LUT: &[i64;76_000] = unsafe { &std::mem::transmute( *include_bytes!("day11_lut.bin") ) };
@@ -2229,6 +3864,72 @@ pub mod day11 {
         unsafe { impl_rec(input, 75) }
     }
 
+    // the LUT has counts for values below 1000 at 0..=75 blinks
+    const LUT_VALUES: u64 = 1000;
+    const LUT_DEPTHS: usize = 76;
+
+    #[derive(Debug,Clone,Copy,PartialEq,Eq)]
+    pub enum Overflow {
+        // a stone's number no longer fits in a u64, blink 0 is the input itself
+        Stone{blink: usize},
+        // the number of stones no longer fits in a u128
+        Count{blink: usize}
+    }
+
+    // stone count after any number of blinks. evolves a map of value -> count,
+    // handing stones to the LUT once it covers the blinks left
+    pub fn blink(input: &str, blinks: usize) -> Result<u128,Overflow> {
+        let mut stones = AHashMap::<u64,u128>::new();
+        for word in input.split_ascii_whitespace() {
+            let value = match word.parse::<u64>() {
+                Ok(value) => value,
+                Err(e) if *e.kind() == IntErrorKind::PosOverflow => return Err(Overflow::Stone{blink: 0}),
+                Err(_) => panic!("stone {}",word)
+            };
+            *stones.entry(value).or_default() += 1;
+        }
+
+        let mut sum: u128 = 0;
+        let mut next = AHashMap::new();
+        for blink in 0..=blinks {
+            let left = blinks - blink;
+            next.clear();
+            for (&value,&count) in &stones {
+                let each = if left == 0 {
+                    Some(1)
+                } else if left < LUT_DEPTHS && value < LUT_VALUES {
+                    Some(LUT[value as usize + left * 1000] as u128)
+                } else {
+                    None
+                };
+                if let Some(each) = each {
+                    sum = count.checked_mul(each).and_then(|n| sum.checked_add(n)).ok_or(Overflow::Count{blink})?;
+                    continue;
+                }
+
+                let mut add = |value: u64| {
+                    let total: &mut u128 = next.entry(value).or_default();
+                    *total = total.checked_add(count).ok_or(Overflow::Count{blink: blink+1})?;
+                    Ok(())
+                };
+                if value == 0 {
+                    add(1)?;
+                } else {
+                    let digits = value.ilog10() + 1;
+                    if digits%2 == 0 {
+                        let divisor = 10u64.pow(digits/2);
+                        add(value / divisor)?;
+                        add(value % divisor)?;
+                    } else {
+                        add(value.checked_mul(2024).ok_or(Overflow::Stone{blink: blink+1})?)?;
+                    }
+                }
+            }
+            std::mem::swap(&mut stones, &mut next);
+        }
+        Ok(sum)
+    }
+
     #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
     unsafe fn impl_rec(input: &str, count: i32) -> i64 {
         let input = input.as_bytes();
@@ -3115,126 +4816,1276 @@ pub mod day13 {
     }
 }
 
-// 14-16 skipped
+// 14-15 skipped
 
-pub mod day17;
+pub mod day16 {
+    use crate::pathfind::{Dijkstra, Edges, UNREACHED};
 
-pub mod day22 {
-    fn prune_mix(a: u32,b: u32) -> u32 {
-        (a^b)&16777215
+    pub fn part1(input: &str) -> i64 {
+        unsafe { impl1(input) }
     }
-    
-    fn do_round(x: u32) -> u32 {
-        // phase 1
-        let x = prune_mix(x,x<<6);
-        // phase 2
-        let x = prune_mix(x,x>>5);
-        // phase 3
-        let x = prune_mix(x,x<<11);
-        x
+
+    pub fn part2(input: &str) -> i64 {
+        unsafe { impl2(input) }
     }
 
-    fn do2k(mut x: u32) -> u32 {
-        for _ in 0..2000 {
-            x = do_round(x);
-        }
-        x
+    const STEP_COST: u32 = 1;
+    const TURN_COST: u32 = 1000;
+
+    // states are byte_index*4 + dir, dirs go clockwise from east so that dir^2 is the opposite
+    const EAST: u32 = 0;
+
+    struct Maze<'a> {
+        bytes: &'a [u8],
+        stride: usize,
+        start: usize,
+        end: usize
     }
 
-    static LUT: &[u32;16777216] = unsafe { &std::mem::transmute( *include_bytes!("day22_lut.bin") ) };
-    fn generate_lut() {
-        let mut lut = vec!(0;16777216);
-        for i in 0..16777216 {
-            if i % 100_000 == 0 {
-                println!("{}",i);
+    impl<'a> Maze<'a> {
+        fn new(input: &'a str) -> Self {
+            let bytes = input.as_bytes();
+            let stride = bytes.iter().position(|b| *b == b'\n').unwrap() + 1;
+            let start = bytes.iter().position(|b| *b == b'S').unwrap();
+            let end = bytes.iter().position(|b| *b == b'E').unwrap();
+            Self {
+                bytes,
+                stride,
+                start,
+                end
             }
-            lut[i as usize] = do2k(i);
         }
-        let bytes: &[u8] = unsafe { std::slice::from_raw_parts(lut.as_ptr() as _,lut.len() * 4) };
-        std::fs::write("day22_lut.bin", bytes).unwrap();
-        panic!("done");
-    }
 
-    static mut MAP: [u16;19*19*19*19] = [0;19*19*19*19];
-    static mut SET: [u64;2037] = [0;2037];
-    fn set_contains(index: i32) -> bool {
-        let cell = index / 64;
-        let bit = index % 64;
-        unsafe {
-            SET[cell as usize] & (1 << bit) != 0
+        fn state_count(&self) -> usize {
+            self.bytes.len() * 4
         }
-    }
 
-    fn set_insert(index: i32) {
-        let cell = index / 64;
-        let bit = index % 64;
-        unsafe {
-            SET[cell as usize] |= (1 << bit);
+        #[inline(always)]
+        fn edges(&self, state: u32, out: &mut Edges) {
+            let index = (state >> 2) as usize;
+            let dir = state & 3;
+            // the maze is walled in, so stepping forward never leaves the input
+            let next = match dir {
+                0 => index + 1,
+                1 => index + self.stride,
+                2 => index - 1,
+                _ => index - self.stride
+            };
+            if self.bytes[next] != b'#' {
+                out.push(((next as u32) << 2 | dir, STEP_COST));
+            }
+            out.push((state & !3 | ((dir+1) & 3), TURN_COST));
+            out.push((state & !3 | ((dir+3) & 3), TURN_COST));
         }
     }
 
-    fn do2k_part2(mut x: u32) -> u32 {
-        let mut last_digit = (x%10) as i32;
-        unsafe {
-            SET.fill(0);
-        }
-        let mut hash = 0;
-        for i in 0..2000 {
-            x = do_round(x);
-            let digit = (x%10) as i32;
-            let d = digit - last_digit;
-            last_digit = digit;
-            hash = (hash * 19 + d+9) % (19*19*19*19);
-            if i >= 3 {
-                if !set_contains(hash) {
-                    set_insert(hash);
-                    unsafe {
-                        MAP[hash as usize] += digit as u16;
-                    }
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl1(input: &str) -> i64 {
+        let maze = Maze::new(input);
+        let mut search = Dijkstra::new(maze.state_count(), TURN_COST);
+
+        let start = (maze.start as u32) << 2 | EAST;
+        let end = maze.end as u32;
+        let (_,cost) = search.search([start], |s,out| maze.edges(s,out), |s| s >> 2 == end).unwrap();
+        cost as i64
+    }
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl2(input: &str) -> i64 {
+        let maze = Maze::new(input);
+        let mut forward = Dijkstra::new(maze.state_count(), TURN_COST);
+        let mut backward = Dijkstra::new(maze.state_count(), TURN_COST);
+
+        let start = (maze.start as u32) << 2 | EAST;
+        let end = (maze.end as u32) << 2;
+
+        forward.search([start], |s,out| maze.edges(s,out), |_| false);
+        // walking backwards from the end with every direction flipped mirrors the forward graph,
+        // so the same edge function gives distances *to* the end
+        backward.search(end..end+4, |s,out| maze.edges(s,out), |_| false);
+
+        let best = forward.dist[end as usize..end as usize + 4].iter().copied().min().unwrap();
+
+        let mut count = 0;
+        for index in 0..maze.bytes.len() {
+            let base = index * 4;
+            for dir in 0..4 {
+                let f = forward.dist[base + dir];
+                let b = backward.dist[base + (dir^2)];
+                if f != UNREACHED && b != UNREACHED && f + b == best {
+                    count += 1;
+                    break;
                 }
             }
         }
-        x
+        count
     }
+}
+
+pub mod day17;
+
+pub mod day18 {
+    use crate::pathfind::{Dijkstra, Edges};
+
+    pub const SIZE: usize = 71;
+    pub const FALLEN: usize = 1024;
 
     pub fn part1(input: &str) -> i64 {
-        //generate_lut();
+        solve1(input, SIZE, FALLEN)
+    }
+
+    pub fn part2(input: &str) -> &str {
+        solve2(input, SIZE)
+    }
+
+    struct Byte {
+        x: u32,
+        y: u32,
+        // line in the input, which is also the answer format for part 2
+        start: u32,
+        end: u32
+    }
+
+    fn parse(input: &str) -> Vec<Byte> {
+        let bytes = input.as_bytes();
+        let mut result = Vec::with_capacity(4096);
         let mut index = 0;
-        let input = input.as_bytes();
-        let mut n = 0;
-        let mut sum = 0;
-        while index < input.len() {
-            let digit = input[index];
+        while index < bytes.len() {
+            let start = index;
+            let mut x = 0;
+            while bytes[index] != b',' {
+                x = x * 10 + (bytes[index] - b'0') as u32;
+                index += 1;
+            }
             index += 1;
-            if digit == b'\n' {
-                sum += LUT[n as usize] as i64;
-                n = 0;
-            } else {
-                n = 10*n + (digit-b'0') as u32;
+            let mut y = 0;
+            while index < bytes.len() && bytes[index] != b'\n' {
+                y = y * 10 + (bytes[index] - b'0') as u32;
+                index += 1;
             }
+            result.push(Byte{
+                x,
+                y,
+                start: start as u32,
+                end: index as u32
+            });
+            index += 1;
         }
-        sum
+        result
     }
 
-    pub fn part2(input: &str) -> i64 {
-        let mut index = 0;
-        let input = input.as_bytes();
-        let mut n = 0;
-        unsafe {
-            MAP.fill(0);
+    pub fn solve1(input: &str, size: usize, fallen: usize) -> i64 {
+        let mut blocked = vec![false;size*size];
+        for byte in parse(input).iter().take(fallen) {
+            blocked[byte.y as usize * size + byte.x as usize] = true;
         }
 
-        while index < input.len() {
-            let digit = input[index];
-            index += 1;
-            if digit == b'\n' {
-                do2k_part2(n);
-                n = 0;
-            } else {
-                n = 10*n + (digit-b'0') as u32;
+        // unit costs, so the bucket queue degenerates into a plain bfs
+        let mut search = Dijkstra::new(size*size, 1);
+        let goal = (size*size - 1) as u32;
+        let edges = |state: u32, out: &mut Edges| {
+            let index = state as usize;
+            let (x,y) = (index % size, index / size);
+            if x > 0 && !blocked[index-1] { out.push((state-1,1)); }
+            if x < size-1 && !blocked[index+1] { out.push((state+1,1)); }
+            if y > 0 && !blocked[index-size] { out.push((state-size as u32,1)); }
+            if y < size-1 && !blocked[index+size] { out.push((state+size as u32,1)); }
+        };
+        let (_,cost) = search.search([0], edges, |s| s == goal).expect("exit is unreachable");
+        cost as i64
+    }
+
+    struct UnionFind {
+        parent: Vec<u32>
+    }
+
+    impl UnionFind {
+        fn new(n: usize) -> Self {
+            Self {
+                parent: (0..n as u32).collect()
             }
         }
-        let max = unsafe { MAP.iter().copied().max() };
-        max.unwrap() as i64
+
+        fn find(&mut self, mut id: u32) -> u32 {
+            while self.parent[id as usize] != id {
+                // path halving
+                let grandparent = self.parent[self.parent[id as usize] as usize];
+                self.parent[id as usize] = grandparent;
+                id = grandparent;
+            }
+            id
+        }
+
+        fn union(&mut self, a: u32, b: u32) {
+            let a = self.find(a);
+            let b = self.find(b);
+            self.parent[a as usize] = b;
+        }
+    }
+
+    // drop every byte, then lift them back off in reverse order until the corners join up
+    pub fn solve2(input: &str, size: usize) -> &str {
+        let bytes = parse(input);
+
+        // a cell is only freed when its earliest byte is lifted
+        let mut blocked_at = vec![usize::MAX;size*size];
+        for (i,byte) in bytes.iter().enumerate() {
+            let cell = &mut blocked_at[byte.y as usize * size + byte.x as usize];
+            *cell = (*cell).min(i);
+        }
+
+        let mut sets = UnionFind::new(size*size);
+        let open = |blocked_at: &[usize], index: usize| blocked_at[index] == usize::MAX;
+
+        let join = |sets: &mut UnionFind, blocked_at: &[usize], index: usize| {
+            let (x,y) = (index % size, index / size);
+            if x > 0 && open(blocked_at,index-1) { sets.union(index as u32,(index-1) as u32); }
+            if x < size-1 && open(blocked_at,index+1) { sets.union(index as u32,(index+1) as u32); }
+            if y > 0 && open(blocked_at,index-size) { sets.union(index as u32,(index-size) as u32); }
+            if y < size-1 && open(blocked_at,index+size) { sets.union(index as u32,(index+size) as u32); }
+        };
+
+        for index in 0..size*size {
+            if open(&blocked_at,index) {
+                join(&mut sets,&blocked_at,index);
+            }
+        }
+
+        let goal = (size*size - 1) as u32;
+        assert!(sets.find(0) != sets.find(goal), "exit is never cut off");
+
+        for (i,byte) in bytes.iter().enumerate().rev() {
+            let index = byte.y as usize * size + byte.x as usize;
+            if blocked_at[index] != i {
+                continue;
+            }
+            blocked_at[index] = usize::MAX;
+            join(&mut sets,&blocked_at,index);
+            if sets.find(0) == sets.find(goal) {
+                return &input[byte.start as usize..byte.end as usize];
+            }
+        }
+        panic!("start is blocked");
+    }
+}
+
+pub mod day19 {
+    pub fn part1(input: &str) -> i64 {
+        unsafe { impl1(input) }
+    }
+
+    pub fn part2(input: &str) -> i64 {
+        unsafe { impl2(input) }
+    }
+
+    const NONE: u16 = 0;
+
+    fn colour(b: u8) -> usize {
+        match b {
+            b'w' => 0,
+            b'u' => 1,
+            b'b' => 2,
+            b'r' => 3,
+            b'g' => 4,
+            _ => panic!("char {}",b as char)
+        }
+    }
+
+    #[derive(Clone,Copy)]
+    struct Node {
+        // the root is never a child, so 0 doubles as "no edge"
+        next: [u16;5],
+        is_towel: bool
+    }
+
+    struct Trie {
+        nodes: Vec<Node>
+    }
+
+    impl Trie {
+        fn new() -> Self {
+            let mut nodes = Vec::with_capacity(1024);
+            nodes.push(Node{ next: [NONE;5], is_towel: false });
+            Self { nodes }
+        }
+
+        fn insert(&mut self, towel: &[u8]) {
+            let mut node = 0;
+            for b in towel.iter().copied() {
+                let c = colour(b);
+                let next = self.nodes[node].next[c];
+                node = if next == NONE {
+                    let new_index = self.nodes.len();
+                    self.nodes.push(Node{ next: [NONE;5], is_towel: false });
+                    self.nodes[node].next[c] = new_index as u16;
+                    new_index
+                } else {
+                    next as usize
+                };
+            }
+            self.nodes[node].is_towel = true;
+        }
+
+        // number of ways to build the design from towels, ways needs room for every prefix
+        #[inline(always)]
+        fn arrangements(&self, design: &[u8], ways: &mut [u64]) -> u64 {
+            let ways = &mut ways[..=design.len()];
+            ways.fill(0);
+            ways[0] = 1;
+            for start in 0..design.len() {
+                let w = ways[start];
+                if w == 0 {
+                    continue;
+                }
+                let mut node = 0;
+                for (i,b) in design[start..].iter().copied().enumerate() {
+                    let next = self.nodes[node].next[colour(b)];
+                    if next == NONE {
+                        break;
+                    }
+                    node = next as usize;
+                    if self.nodes[node].is_towel {
+                        ways[start+i+1] += w;
+                    }
+                }
+            }
+            ways[design.len()]
+        }
+    }
+
+    // returns the trie and the remaining design lines
+    fn parse(input: &str) -> (Trie,&[u8]) {
+        let bytes = input.as_bytes();
+        let mut trie = Trie::new();
+        let mut index = 0;
+        loop {
+            let start = index;
+            while bytes[index] != b',' && bytes[index] != b'\n' {
+                index += 1;
+            }
+            trie.insert(&bytes[start..index]);
+            if bytes[index] == b'\n' {
+                break;
+            }
+            // skip ", "
+            index += 2;
+        }
+        // skip blank line
+        (trie,&bytes[index+2..])
+    }
+
+    fn for_each_design(mut bytes: &[u8], mut f: impl FnMut(&[u8])) {
+        while !bytes.is_empty() {
+            let len = bytes.iter().position(|b| *b == b'\n').unwrap_or(bytes.len());
+            f(&bytes[..len]);
+            bytes = &bytes[(len+1).min(bytes.len())..];
+        }
+    }
+
+    // one buffer sized for the longest design, shared by all of them
+    fn ways_buffer(designs: &[u8]) -> Vec<u64> {
+        let mut longest = 0;
+        for_each_design(designs, |design| longest = longest.max(design.len()));
+        vec![0;longest+1]
+    }
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl1(input: &str) -> i64 {
+        let (trie,designs) = parse(input);
+        let mut ways = ways_buffer(designs);
+        let mut count = 0;
+        for_each_design(designs, |design| {
+            if trie.arrangements(design, &mut ways) > 0 {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl2(input: &str) -> i64 {
+        let (trie,designs) = parse(input);
+        let mut ways = ways_buffer(designs);
+        let mut sum = 0;
+        for_each_design(designs, |design| {
+            sum += trie.arrangements(design, &mut ways);
+        });
+        sum as i64
+    }
+}
+
+pub mod day20 {
+    use core::simd::prelude::*;
+
+    pub const THRESHOLD: i32 = 100;
+
+    pub fn part1(input: &str) -> i64 {
+        solve(input, 2, THRESHOLD)
+    }
+
+    pub fn part2(input: &str) -> i64 {
+        solve(input, 20, THRESHOLD)
+    }
+
+    const LANES: usize = 8;
+    // walls sit far enough below zero that no cheat into them can ever pay off
+    const WALL: i32 = -(1<<28);
+    const NO_CELL: i32 = 1<<28;
+
+    // counts cheats of up to max_cheat picoseconds that save at least threshold
+    pub fn solve(input: &str, max_cheat: usize, threshold: i32) -> i64 {
+        unsafe { impl_solve(input, max_cheat, threshold) }
+    }
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl_solve(input: &str, max_cheat: usize, threshold: i32) -> i64 {
+        let bytes = input.as_bytes();
+        let width = bytes.iter().position(|b| *b == b'\n').unwrap();
+        let stride = width + 1;
+        let height = (bytes.len() + 1) / stride;
+
+        // pad so that every window read stays in bounds, plus one vector of slack on the right
+        let pad = max_cheat;
+        let padded_width = pad + width + pad + LANES;
+        let padded_index = |index: usize| (index / stride + pad) * padded_width + index % stride + pad;
+
+        let mut dist = vec![WALL;(pad + height + pad) * padded_width];
+        let mut path = Vec::with_capacity(10_000);
+
+        // the track is a single corridor, so just follow it from the start
+        {
+            let mut index = bytes.iter().position(|b| *b == b'S').unwrap();
+            let mut prev = usize::MAX;
+            loop {
+                dist[padded_index(index)] = path.len() as i32;
+                path.push(padded_index(index));
+                if bytes[index] == b'E' {
+                    break;
+                }
+                let next = [index + 1, index - 1, index + stride, index - stride].into_iter()
+                    .find(|n| *n != prev && bytes[*n] != b'#')
+                    .unwrap();
+                prev = index;
+                index = next;
+            }
+        }
+
+        // per-row window costs, lanes past the diamond edge get a cost no cell can beat
+        let row_lanes = (2 * max_cheat + 1).div_ceil(LANES) * LANES;
+        let mut costs = vec![NO_CELL;(2 * max_cheat + 1) * row_lanes];
+        for dy in 0..=2 * max_cheat {
+            let ady = dy.abs_diff(max_cheat);
+            let span = max_cheat - ady;
+            for dx in 0..=2 * span {
+                costs[dy * row_lanes + dx] = (dx.abs_diff(span) + ady) as i32;
+            }
+        }
+
+        let mut count = 0;
+        for (step,center) in path.iter().copied().enumerate() {
+            let need = i32x8::splat(step as i32 + threshold);
+            for dy in 0..=2 * max_cheat {
+                let span = max_cheat - dy.abs_diff(max_cheat);
+                let row_start = center + dy * padded_width - max_cheat * padded_width - span;
+                let cost_row = &costs[dy * row_lanes..];
+                for lane in (0..=2 * span).step_by(LANES) {
+                    let d = i32x8::from_slice(&dist[row_start + lane..]);
+                    let c = i32x8::from_slice(&cost_row[lane..]);
+                    count += (d - c).simd_ge(need).to_bitmask().count_ones() as i64;
+                }
+            }
+        }
+        count
+    }
+}
+
+pub mod day21 {
+    pub fn part1(input: &str) -> i64 {
+        solve(input, 2)
+    }
+
+    pub fn part2(input: &str) -> i64 {
+        solve(input, 25)
+    }
+
+    pub struct Keypad {
+        // rows top to bottom, b' ' marks the gap the arm must never cross
+        pub layout: &'static [[u8;3]]
+    }
+
+    pub const NUMERIC: Keypad = Keypad{
+        layout: &[
+            *b"789",
+            *b"456",
+            *b"123",
+            *b" 0A"
+        ]
+    };
+
+    pub const DIRECTIONAL: Keypad = Keypad{
+        layout: &[
+            *b" ^A",
+            *b"<v>"
+        ]
+    };
+
+    // indexed by key slot (row*3 + col) on the keypad the table was built for
+    pub type CostTable = [[u64;12];12];
+
+    impl Keypad {
+        pub fn position(&self, key: u8) -> (usize,usize) {
+            for (y,row) in self.layout.iter().enumerate() {
+                if let Some(x) = row.iter().position(|k| *k == key) {
+                    return (x,y);
+                }
+            }
+            panic!("key {}",key as char)
+        }
+
+        pub fn slot(&self, key: u8) -> usize {
+            let (x,y) = self.position(key);
+            y*3 + x
+        }
+
+        fn is_gap(&self, (x,y): (usize,usize)) -> bool {
+            self.layout[y][x] == b' '
+        }
+
+        // cost of moving from one key to another and pressing it, given the
+        // costs of the directional keypad driving this one
+        pub fn costs(&self, below: &CostTable) -> CostTable {
+            let mut table = [[0;12];12];
+
+            let press_cost = |moves: &[(u8,usize)]| {
+                let mut cost = 0;
+                let mut prev = DIRECTIONAL.slot(b'A');
+                for (key,count) in moves.iter().copied() {
+                    if count == 0 {
+                        continue;
+                    }
+                    let slot = DIRECTIONAL.slot(key);
+                    cost += below[prev][slot] + below[slot][slot] * (count as u64 - 1);
+                    prev = slot;
+                }
+                cost + below[prev][DIRECTIONAL.slot(b'A')]
+            };
+
+            for (ay,row_a) in self.layout.iter().enumerate() {
+                for (ax,key_a) in row_a.iter().copied().enumerate() {
+                    if key_a == b' ' {
+                        continue;
+                    }
+                    for (by,row_b) in self.layout.iter().enumerate() {
+                        for (bx,key_b) in row_b.iter().copied().enumerate() {
+                            if key_b == b' ' {
+                                continue;
+                            }
+                            let horizontal = (if bx > ax { b'>' } else { b'<' }, ax.abs_diff(bx));
+                            let vertical = (if by > ay { b'v' } else { b'^' }, ay.abs_diff(by));
+
+                            // mixing the two axes only adds turns, so one of the two L shapes is optimal
+                            let mut best = u64::MAX;
+                            if !self.is_gap((bx,ay)) {
+                                best = best.min(press_cost(&[horizontal,vertical]));
+                            }
+                            if !self.is_gap((ax,by)) {
+                                best = best.min(press_cost(&[vertical,horizontal]));
+                            }
+                            table[ay*3 + ax][by*3 + bx] = best;
+                        }
+                    }
+                }
+            }
+            table
+        }
+    }
+
+    // one table per directional layer: [0] is the human (every press costs 1),
+    // [n] is the keypad worked by the nth robot
+    pub fn directional_tables(depth: usize) -> Vec<CostTable> {
+        let mut tables = Vec::with_capacity(depth + 1);
+        tables.push([[1;12];12]);
+        for i in 0..depth {
+            let next = DIRECTIONAL.costs(&tables[i]);
+            tables.push(next);
+        }
+        tables
+    }
+
+    pub fn numeric_table(depth: usize) -> CostTable {
+        NUMERIC.costs(directional_tables(depth).last().unwrap())
+    }
+
+    // presses needed at the top of the chain to type a code, starting from A
+    pub fn code_cost(code: &[u8], numeric: &CostTable) -> u64 {
+        let mut prev = NUMERIC.slot(b'A');
+        let mut cost = 0;
+        for key in code.iter().copied() {
+            let slot = NUMERIC.slot(key);
+            cost += numeric[prev][slot];
+            prev = slot;
+        }
+        cost
+    }
+
+    pub fn solve(input: &str, depth: usize) -> i64 {
+        let table = numeric_table(depth);
+        let mut sum = 0;
+        for code in input.as_bytes().split(|b| *b == b'\n') {
+            if code.is_empty() {
+                continue;
+            }
+            let mut value = 0;
+            for d in code.iter().copied() {
+                if d.is_ascii_digit() {
+                    value = value * 10 + (d - b'0') as u64;
+                }
+            }
+            sum += code_cost(code, &table) * value;
+        }
+        sum as i64
+    }
+}
+
+pub mod day22 {
+    fn prune_mix(a: u32,b: u32) -> u32 {
+        (a^b)&16777215
+    }
+    
+    fn do_round(x: u32) -> u32 {
+        // phase 1
+        let x = prune_mix(x,x<<6);
+        // phase 2
+        let x = prune_mix(x,x>>5);
+        // phase 3
+        let x = prune_mix(x,x<<11);
+        x
+    }
+
+    fn do2k(mut x: u32) -> u32 {
+        for _ in 0..2000 {
+            x = do_round(x);
+        }
+        x
+    }
+
+    static LUT: &[u32;16777216] = unsafe { &std::mem::transmute( *include_bytes!("day22_lut.bin") ) };
+    fn generate_lut() {
+        let mut lut = vec!(0;16777216);
+        for i in 0..16777216 {
+            if i % 100_000 == 0 {
+                println!("{}",i);
+            }
+            lut[i as usize] = do2k(i);
+        }
+        let bytes: &[u8] = unsafe { std::slice::from_raw_parts(lut.as_ptr() as _,lut.len() * 4) };
+        std::fs::write("day22_lut.bin", bytes).unwrap();
+        panic!("done");
+    }
+
+    static mut MAP: [u16;19*19*19*19] = [0;19*19*19*19];
+    static mut SET: [u64;2037] = [0;2037];
+    fn set_contains(index: i32) -> bool {
+        let cell = index / 64;
+        let bit = index % 64;
+        unsafe {
+            SET[cell as usize] & (1 << bit) != 0
+        }
+    }
+
+    fn set_insert(index: i32) {
+        let cell = index / 64;
+        let bit = index % 64;
+        unsafe {
+            SET[cell as usize] |= (1 << bit);
+        }
+    }
+
+    fn do2k_part2(mut x: u32) -> u32 {
+        let mut last_digit = (x%10) as i32;
+        unsafe {
+            SET.fill(0);
+        }
+        let mut hash = 0;
+        for i in 0..2000 {
+            x = do_round(x);
+            let digit = (x%10) as i32;
+            let d = digit - last_digit;
+            last_digit = digit;
+            hash = (hash * 19 + d+9) % (19*19*19*19);
+            if i >= 3 {
+                if !set_contains(hash) {
+                    set_insert(hash);
+                    unsafe {
+                        MAP[hash as usize] += digit as u16;
+                    }
+                }
+            }
+        }
+        x
+    }
+
+    pub fn part1(input: &str) -> i64 {
+        //generate_lut();
+        let mut index = 0;
+        let input = input.as_bytes();
+        let mut n = 0;
+        let mut sum = 0;
+        while index < input.len() {
+            let digit = input[index];
+            index += 1;
+            if digit == b'\n' {
+                sum += LUT[n as usize] as i64;
+                n = 0;
+            } else {
+                n = 10*n + (digit-b'0') as u32;
+            }
+        }
+        sum
+    }
+
+    pub fn part2(input: &str) -> i64 {
+        let mut index = 0;
+        let input = input.as_bytes();
+        let mut n = 0;
+        unsafe {
+            MAP.fill(0);
+        }
+
+        while index < input.len() {
+            let digit = input[index];
+            index += 1;
+            if digit == b'\n' {
+                do2k_part2(n);
+                n = 0;
+            } else {
+                n = 10*n + (digit-b'0') as u32;
+            }
+        }
+        let max = unsafe { MAP.iter().copied().max() };
+        max.unwrap() as i64
+    }
+}
+
+pub mod day23 {
+    // two lowercase letters per name
+    const NODES: usize = 26*26;
+    const WORDS: usize = NODES.div_ceil(64);
+
+    type Set = [u64;WORDS];
+
+    // adjacency bitsets, one row per computer
+    static mut ADJ: [Set;NODES] = [[0;WORDS];NODES];
+
+    pub fn part1(input: &str) -> i64 {
+        unsafe { impl1(input) }
+    }
+
+    pub fn part2(input: &str) -> String {
+        unsafe { impl2(input) }
+    }
+
+    fn node_id(name: &[u8]) -> usize {
+        (name[0]-b'a') as usize * 26 + (name[1]-b'a') as usize
+    }
+
+    fn has_t(id: usize) -> bool {
+        id / 26 == (b't'-b'a') as usize
+    }
+
+    #[inline(always)]
+    fn insert(set: &mut Set, id: usize) {
+        set[id / 64] |= 1 << (id % 64);
+    }
+
+    #[inline(always)]
+    fn remove(set: &mut Set, id: usize) {
+        set[id / 64] &= !(1 << (id % 64));
+    }
+
+    #[inline(always)]
+    fn and(a: &Set, b: &Set) -> Set {
+        std::array::from_fn(|i| a[i] & b[i])
+    }
+
+    #[inline(always)]
+    fn count(set: &Set) -> u32 {
+        set.iter().map(|w| w.count_ones()).sum()
+    }
+
+    #[inline(always)]
+    fn is_empty(set: &Set) -> bool {
+        set.iter().all(|w| *w == 0)
+    }
+
+    fn for_each(set: &Set, mut f: impl FnMut(usize)) {
+        for (i,word) in set.iter().copied().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                f(i*64 + word.trailing_zeros() as usize);
+                word &= word - 1;
+            }
+        }
+    }
+
+    // returns the set of computers that appear at all
+    unsafe fn parse(input: &str) -> Set {
+        ADJ = [[0;WORDS];NODES];
+        let mut present = [0;WORDS];
+        // "ab-cd\n"
+        for line in input.as_bytes().chunks(6) {
+            if line.len() < 5 {
+                break;
+            }
+            let a = node_id(&line[0..2]);
+            let b = node_id(&line[3..5]);
+            insert(&mut ADJ[a], b);
+            insert(&mut ADJ[b], a);
+            insert(&mut present, a);
+            insert(&mut present, b);
+        }
+        present
+    }
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl1(input: &str) -> i64 {
+        let present = parse(input);
+        let mut triangles = 0;
+        for_each(&present, |a| {
+            for_each(&ADJ[a], |b| {
+                if b <= a {
+                    return;
+                }
+                let common = and(&ADJ[a], &ADJ[b]);
+                for_each(&common, |c| {
+                    if c > b && (has_t(a) || has_t(b) || has_t(c)) {
+                        triangles += 1;
+                    }
+                });
+            });
+        });
+        triangles
+    }
+
+    // bron-kerbosch with pivoting, every set is a bitset over node ids
+    unsafe fn bron_kerbosch(r: &mut Set, mut p: Set, mut x: Set, best: &mut Set) {
+        if is_empty(&p) && is_empty(&x) {
+            if count(r) > count(best) {
+                *best = *r;
+            }
+            return;
+        }
+        // can't beat the best clique even by taking every candidate
+        if count(r) + count(&p) <= count(best) {
+            return;
+        }
+
+        // pivot on the node with the most neighbours among the candidates
+        let mut pivot = 0;
+        let mut pivot_degree = 0;
+        let union: Set = std::array::from_fn(|i| p[i] | x[i]);
+        for_each(&union, |u| {
+            let degree = count(&and(&p, &ADJ[u]));
+            if degree >= pivot_degree {
+                pivot = u;
+                pivot_degree = degree;
+            }
+        });
+
+        let candidates: Set = std::array::from_fn(|i| p[i] & !ADJ[pivot][i]);
+        for_each(&candidates, |v| {
+            insert(r, v);
+            bron_kerbosch(r, and(&p, &ADJ[v]), and(&x, &ADJ[v]), best);
+            remove(r, v);
+            remove(&mut p, v);
+            insert(&mut x, v);
+        });
+    }
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl2(input: &str) -> String {
+        let present = parse(input);
+        let mut best = [0;WORDS];
+        bron_kerbosch(&mut [0;WORDS], present, [0;WORDS], &mut best);
+
+        // ids are already in alphabetical order
+        let mut password = String::with_capacity(64);
+        for_each(&best, |id| {
+            if !password.is_empty() {
+                password.push(',');
+            }
+            password.push((b'a' + (id / 26) as u8) as char);
+            password.push((b'a' + (id % 26) as u8) as char);
+        });
+        password
+    }
+}
+
+pub mod day24 {
+    use rand::Rng;
+
+    pub fn part1(input: &str) -> i64 {
+        Netlist::parse(input).evaluate_initial() as i64
+    }
+
+    pub fn part2(input: &str) -> String {
+        let netlist = Netlist::parse(input);
+        let mut names: Vec<&str> = netlist.suspect_outputs().iter().map(|w| netlist.name(*w)).collect();
+        names.sort();
+        names.join(",")
+    }
+
+    #[derive(Debug,Clone,Copy,PartialEq,Eq)]
+    pub enum Op {
+        And,
+        Or,
+        Xor
+    }
+
+    #[derive(Debug,Clone,Copy)]
+    pub struct Gate {
+        pub a: u16,
+        pub b: u16,
+        pub op: Op,
+        pub out: u16
+    }
+
+    const NO_WIRE: u16 = u16::MAX;
+
+    // wire names are three chars of [0-9a-z]
+    fn wire_key(name: &[u8]) -> usize {
+        let digit = |c: u8| match c {
+            b'0'..=b'9' => (c - b'0') as usize,
+            b'a'..=b'z' => (c - b'a') as usize + 10,
+            _ => panic!("char {}",c as char)
+        };
+        digit(name[0]) * 36 * 36 + digit(name[1]) * 36 + digit(name[2])
+    }
+
+    #[derive(Clone)]
+    pub struct Netlist {
+        names: Vec<[u8;3]>,
+        lookup: Vec<u16>,
+        // always kept in topological order
+        gates: Vec<Gate>,
+        // wire ids by bit, least significant first
+        pub x: Vec<u16>,
+        pub y: Vec<u16>,
+        pub z: Vec<u16>,
+        initial: Vec<(u16,bool)>
+    }
+
+    impl Netlist {
+        pub fn parse(input: &str) -> Self {
+            let mut netlist = Self {
+                names: Vec::with_capacity(512),
+                lookup: vec![NO_WIRE;36*36*36],
+                gates: Vec::with_capacity(256),
+                x: Vec::new(),
+                y: Vec::new(),
+                z: Vec::new(),
+                initial: Vec::with_capacity(128)
+            };
+
+            let mut lines = input.lines();
+            // "x00: 1"
+            for line in lines.by_ref() {
+                let line = line.as_bytes();
+                if line.is_empty() {
+                    break;
+                }
+                let wire = netlist.intern(&line[0..3]);
+                netlist.initial.push((wire,line[5] == b'1'));
+            }
+            // "ntg XOR fgs -> mjb"
+            for line in lines {
+                let line = line.as_bytes();
+                if line.is_empty() {
+                    continue;
+                }
+                let (op,rest) = match line[4] {
+                    b'A' => (Op::And,&line[8..]),
+                    b'O' => (Op::Or,&line[7..]),
+                    b'X' => (Op::Xor,&line[8..]),
+                    c => panic!("char {}",c as char)
+                };
+                let a = netlist.intern(&line[0..3]);
+                let b = netlist.intern(&rest[0..3]);
+                let out = netlist.intern(&rest[7..10]);
+                netlist.gates.push(Gate{a,b,op,out});
+            }
+
+            for (id,name) in netlist.names.iter().enumerate() {
+                let bit = ((name[1] - b'0') * 10 + (name[2] - b'0')) as usize;
+                let bits = match name[0] {
+                    b'x' => &mut netlist.x,
+                    b'y' => &mut netlist.y,
+                    b'z' => &mut netlist.z,
+                    _ => continue
+                };
+                if bits.len() <= bit {
+                    bits.resize(bit + 1, NO_WIRE);
+                }
+                bits[bit] = id as u16;
+            }
+
+            assert!(netlist.sort(), "gate network has a cycle");
+            netlist
+        }
+
+        fn intern(&mut self, name: &[u8]) -> u16 {
+            let key = wire_key(name);
+            if self.lookup[key] == NO_WIRE {
+                self.lookup[key] = self.names.len() as u16;
+                self.names.push([name[0],name[1],name[2]]);
+            }
+            self.lookup[key]
+        }
+
+        pub fn wire(&self, name: &str) -> Option<u16> {
+            let id = self.lookup[wire_key(name.as_bytes())];
+            if id == NO_WIRE { None } else { Some(id) }
+        }
+
+        pub fn name(&self, wire: u16) -> &str {
+            std::str::from_utf8(&self.names[wire as usize]).unwrap()
+        }
+
+        pub fn gates(&self) -> &[Gate] {
+            &self.gates
+        }
+
+        // reorders gates so every input is computed before it is read, false if there is a cycle
+        fn sort(&mut self) -> bool {
+            const UNSEEN: u8 = 0;
+            const VISITING: u8 = 1;
+            const DONE: u8 = 2;
+
+            let mut producer = vec![NO_WIRE;self.names.len()];
+            for (i,gate) in self.gates.iter().enumerate() {
+                producer[gate.out as usize] = i as u16;
+            }
+
+            fn visit(gate: u16, gates: &[Gate], producer: &[u16], state: &mut [u8], order: &mut Vec<Gate>) -> bool {
+                match state[gate as usize] {
+                    DONE => return true,
+                    VISITING => return false,
+                    _ => ()
+                }
+                state[gate as usize] = VISITING;
+                let g = gates[gate as usize];
+                for input in [g.a,g.b] {
+                    let p = producer[input as usize];
+                    if p != NO_WIRE && !visit(p, gates, producer, state, order) {
+                        return false;
+                    }
+                }
+                state[gate as usize] = DONE;
+                order.push(g);
+                true
+            }
+
+            let mut state = vec![UNSEEN;self.gates.len()];
+            let mut order = Vec::with_capacity(self.gates.len());
+            for i in 0..self.gates.len() {
+                if !visit(i as u16, &self.gates, &producer, &mut state, &mut order) {
+                    return false;
+                }
+            }
+            self.gates = order;
+            true
+        }
+
+        // swaps the drivers of two wires, leaving the netlist untouched if that would form a cycle
+        pub fn swap_outputs(&mut self, a: u16, b: u16) -> bool {
+            let swap = |gates: &mut [Gate]| {
+                for gate in gates.iter_mut() {
+                    if gate.out == a {
+                        gate.out = b;
+                    } else if gate.out == b {
+                        gate.out = a;
+                    }
+                }
+            };
+            swap(&mut self.gates);
+            if self.sort() {
+                true
+            } else {
+                swap(&mut self.gates);
+                assert!(self.sort());
+                false
+            }
+        }
+
+        pub fn evaluate(&self, x: u64, y: u64) -> u64 {
+            let mut values = vec![0u8;self.names.len()];
+            for (bit,wire) in self.x.iter().copied().enumerate() {
+                values[wire as usize] = ((x >> bit) & 1) as u8;
+            }
+            for (bit,wire) in self.y.iter().copied().enumerate() {
+                values[wire as usize] = ((y >> bit) & 1) as u8;
+            }
+            for gate in self.gates.iter() {
+                let a = values[gate.a as usize];
+                let b = values[gate.b as usize];
+                values[gate.out as usize] = match gate.op {
+                    Op::And => a & b,
+                    Op::Or => a | b,
+                    Op::Xor => a ^ b
+                };
+            }
+            let mut z = 0;
+            for (bit,wire) in self.z.iter().copied().enumerate() {
+                z |= (values[wire as usize] as u64) << bit;
+            }
+            z
+        }
+
+        pub fn evaluate_initial(&self) -> u64 {
+            let mut x = 0;
+            let mut y = 0;
+            for (wire,value) in self.initial.iter().copied() {
+                let name = self.names[wire as usize];
+                let bit = (name[1] - b'0') * 10 + (name[2] - b'0');
+                match name[0] {
+                    b'x' => x |= (value as u64) << bit,
+                    b'y' => y |= (value as u64) << bit,
+                    _ => ()
+                }
+            }
+            self.evaluate(x, y)
+        }
+
+        // checks the network against real additions, including a full-length carry chain
+        pub fn verify_adder(&self, trials: usize, rng: &mut impl Rng) -> bool {
+            let mask = (1u64 << self.x.len()) - 1;
+            if self.evaluate(mask, 1) != mask + 1 {
+                return false;
+            }
+            for _ in 0..trials {
+                let x = rng.gen::<u64>() & mask;
+                let y = rng.gen::<u64>() & mask;
+                if self.evaluate(x, y) != x + y {
+                    return false;
+                }
+            }
+            true
+        }
+
+        fn is_input(&self, wire: u16) -> bool {
+            let c = self.names[wire as usize][0];
+            c == b'x' || c == b'y'
+        }
+
+        fn is_first_bit(&self, wire: u16) -> bool {
+            let name = self.names[wire as usize];
+            name[1] == b'0' && name[2] == b'0'
+        }
+
+        // outputs that break the shape of a ripple-carry adder:
+        //   z = (x ^ y) ^ carry_in
+        //   carry_out = (x & y) | ((x ^ y) & carry_in)
+        pub fn suspect_outputs(&self) -> Vec<u16> {
+            // which kinds of gate read each wire
+            let mut feeds = vec![0u8;self.names.len()];
+            for gate in self.gates.iter() {
+                feeds[gate.a as usize] |= 1 << gate.op as u8;
+                feeds[gate.b as usize] |= 1 << gate.op as u8;
+            }
+            let last_z = *self.z.last().unwrap();
+
+            let mut result = Vec::with_capacity(8);
+            for gate in self.gates.iter() {
+                let out = gate.out;
+                let is_z = self.names[out as usize][0] == b'z';
+                let from_inputs = self.is_input(gate.a) && self.is_input(gate.b);
+                let first_bit = from_inputs && self.is_first_bit(gate.a);
+                let feeds_op = |op: Op| feeds[out as usize] & (1 << op as u8) != 0;
+
+                let bad = match gate.op {
+                    // the half-sum must meet the carry in another xor, except bit 0 where it is the sum
+                    Op::Xor if from_inputs => !first_bit && !feeds_op(Op::Xor),
+                    // the full sum is always a z, but the top z is the final carry
+                    Op::Xor => !is_z || out == last_z,
+                    // partial carries always merge through an or
+                    Op::And => is_z || (!first_bit && !feeds_op(Op::Or)),
+                    Op::Or => is_z && out != last_z
+                };
+                if bad {
+                    result.push(out);
+                }
+            }
+            result
+        }
+
+        // tries every pairing of the given wires and returns the first netlist that adds correctly
+        pub fn repair(&self, wires: &[u16], trials: usize, rng: &mut impl Rng) -> Option<Netlist> {
+            if wires.is_empty() {
+                return if self.verify_adder(trials, rng) { Some(self.clone()) } else { None };
+            }
+            let first = wires[0];
+            for i in 1..wires.len() {
+                let mut candidate = self.clone();
+                if !candidate.swap_outputs(first, wires[i]) {
+                    continue;
+                }
+                let rest: Vec<u16> = wires[1..].iter().copied().enumerate()
+                    .filter(|(j,_)| *j + 1 != i)
+                    .map(|(_,w)| w)
+                    .collect();
+                if let Some(repaired) = candidate.repair(&rest, trials, rng) {
+                    return Some(repaired);
+                }
+            }
+            None
+        }
+    }
+}
+
+pub mod day25 {
+    use core::simd::prelude::*;
+
+    pub fn part1(input: &str) -> i64 {
+        unsafe { impl1(input) }
+    }
+
+    // 7 rows of 5 + newline, plus the blank separator line
+    const BLOCK: usize = 43;
+    const LANES: usize = 16;
+    // bits for the five middle rows, the newline columns always read as empty
+    const BODY_MASK: u32 = (1<<30) - 1;
+
+    #[target_feature(enable = "avx2,bmi1,bmi2,cmpxchg16b,lzcnt,movbe,popcnt")]
+    unsafe fn impl1(input: &str) -> i64 {
+        let bytes = input.as_bytes();
+        let mut locks = Vec::with_capacity(256);
+        let mut keys = Vec::with_capacity(256 + LANES);
+
+        for block in (0..bytes.len()).step_by(BLOCK) {
+            let body = u8x32::from_slice(&bytes[block+6..]);
+            let mask = body.simd_eq(u8x32::splat(b'#')).to_bitmask() as u32 & BODY_MASK;
+            if bytes[block] == b'#' {
+                locks.push(mask);
+            } else {
+                keys.push(mask);
+            }
+        }
+        // padding lanes are masked out of the last chunk, an empty lock would fit them
+        let tail_lanes = keys.len() % LANES;
+        let last_chunk = keys.len() / LANES;
+        while keys.len() % LANES != 0 {
+            keys.push(BODY_MASK);
+        }
+        let lane_mask = |chunk: usize| if chunk == last_chunk { (1u64<<tail_lanes) - 1 } else { u64::MAX };
+
+        let mut count = 0;
+        for lock in locks.iter().copied() {
+            let lock = u32x16::splat(lock);
+            for (i,chunk) in keys.chunks_exact(LANES).enumerate() {
+                let keys = u32x16::from_slice(chunk);
+                count += ((keys & lock).simd_eq(u32x16::splat(0)).to_bitmask() & lane_mask(i)).count_ones();
+            }
+        }
+        count as i64
     }
 }